@@ -0,0 +1,184 @@
+use crate::loss::Loss;
+
+/// Activation function applied to a neuron's weighted input sum.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    #[default]
+    Sigmoid,
+    Tanh,
+    ReLU,
+    /// Leaky ReLU with the given slope for negative inputs.
+    LeakyReLU(f64),
+}
+
+impl Activation {
+    pub fn apply(&self, x: f64) -> f64 {
+        match *self {
+            Activation::Sigmoid => sigmoid(x),
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(0.0),
+            Activation::LeakyReLU(slope) => if x > 0.0 { x } else { slope * x },
+        }
+    }
+
+    /// Derivative expressed in terms of the activation's output `y`.
+    ///
+    /// ReLU is not differentiable at zero; an output of exactly zero is treated
+    /// as having zero gradient.
+    pub fn derivative(&self, y: f64) -> f64 {
+        match *self {
+            Activation::Sigmoid => y * (1.0 - y),
+            Activation::Tanh => 1.0 - y * y,
+            Activation::ReLU => if y > 0.0 { 1.0 } else { 0.0 },
+            Activation::LeakyReLU(slope) => if y > 0.0 { 1.0 } else { slope },
+        }
+    }
+}
+
+/// Logistic function, evaluated so that `exp` only ever sees a non-positive
+/// argument and cannot overflow.
+pub(crate) fn sigmoid(x: f64) -> f64 {
+    if x >= 0.0 {
+        1.0 / (1.0 + (-x).exp())
+    } else {
+        let e = x.exp();
+        e / (1.0 + e)
+    }
+}
+
+/// Activation applied to the network's final output vector as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputActivation {
+    /// Outputs pass through unchanged.
+    #[default]
+    Identity,
+    /// Element-wise logistic sigmoid.
+    Sigmoid,
+    /// Normalizes the outputs into a probability distribution summing to 1.
+    Softmax,
+}
+
+impl OutputActivation {
+    pub fn apply(&self, x: &[f64]) -> Vec<f64> {
+        match self {
+            OutputActivation::Identity => x.to_vec(),
+            OutputActivation::Sigmoid => x.iter().map(|&v| Activation::Sigmoid.apply(v)).collect(),
+            OutputActivation::Softmax => {
+                // Shifting by the maximum keeps `exp` from overflowing.
+                let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let exps: Vec<f64> = x.iter().map(|&v| (v - max).exp()).collect();
+                let sum: f64 = exps.iter().sum();
+                exps.into_iter().map(|e| e / sum).collect()
+            }
+        }
+    }
+
+    /// Gradient with respect to the activation's inputs, given its output `y`
+    /// and the gradient with respect to that output.
+    pub fn backward(&self, y: &[f64], gradient: &[f64]) -> Vec<f64> {
+        match self {
+            OutputActivation::Identity => gradient.to_vec(),
+            OutputActivation::Sigmoid => y.iter()
+                .zip(gradient.iter())
+                .map(|(&y, &g)| g * Activation::Sigmoid.derivative(y))
+                .collect(),
+            OutputActivation::Softmax => {
+                let dot: f64 = y.iter().zip(gradient.iter()).map(|(&y, &g)| y * g).sum();
+                y.iter().zip(gradient.iter()).map(|(&y, &g)| y * (g - dot)).collect()
+            }
+        }
+    }
+
+    /// Gradient of `loss` with respect to the activation's inputs for the
+    /// pairings that simplify to `(y - t) / n`: sigmoid with binary
+    /// cross-entropy and softmax with cross-entropy. `n` is the number of
+    /// elements the loss averages over. Returns `None` for any other pairing,
+    /// which should go through [`OutputActivation::backward`] instead.
+    ///
+    /// The softmax form assumes `target` sums to 1.
+    pub fn fused_gradient(&self, loss: Loss, y: &[f64], target: &[f64], n: usize) -> Option<Vec<f64>> {
+        match (self, loss) {
+            (OutputActivation::Sigmoid, Loss::BinaryCrossEntropy) | (OutputActivation::Softmax, Loss::CrossEntropy) => {
+                let mut gradient = vec![0.0; y.len()];
+                for (g, (&y, &t)) in gradient.iter_mut().zip(y.iter().zip(target.iter())) {
+                    *g = (y - t) / n as f64;
+                }
+                Some(gradient)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivatives_match_finite_differences() {
+        let epsilon = 1e-6;
+        for activation in [Activation::Sigmoid, Activation::Tanh, Activation::ReLU, Activation::LeakyReLU(0.01)] {
+            for &x in &[-2.0, -0.5, 0.3, 1.7] {
+                let numeric = (activation.apply(x + epsilon) - activation.apply(x - epsilon)) / (2.0 * epsilon);
+                let analytic = activation.derivative(activation.apply(x));
+                assert!((numeric - analytic).abs() < 1e-4, "{:?} at {}", activation, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sigmoid_saturates_without_overflow() {
+        for &x in &[-1000.0, 1000.0, -40.0, 40.0] {
+            let y = sigmoid(x);
+            assert!(y.is_finite() && (0.0..=1.0).contains(&y), "sigmoid({}) = {}", x, y);
+            assert!(Activation::Sigmoid.derivative(y).is_finite());
+        }
+        // exp(-40) is still representable, so the small side keeps its precision.
+        assert!(sigmoid(-40.0) > 0.0 && (sigmoid(-40.0) - (-40.0f64).exp()).abs() < 1e-30);
+        assert_eq!(sigmoid(0.0), 0.5);
+    }
+
+    #[test]
+    fn test_softmax_sums_to_one_and_backward_matches_finite_differences() {
+        let x = [0.5, -1.0, 2.0];
+        let y = OutputActivation::Softmax.apply(&x);
+        assert!((y.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // Gradient of sum(w * softmax(x)) for arbitrary weights w.
+        let w = [0.3, -0.7, 1.1];
+        let objective = |x: &[f64]| OutputActivation::Softmax.apply(x).iter().zip(w.iter()).map(|(y, w)| y * w).sum::<f64>();
+        let analytic = OutputActivation::Softmax.backward(&y, &w);
+        let epsilon = 1e-6;
+        for i in 0..x.len() {
+            let mut plus = x;
+            let mut minus = x;
+            plus[i] += epsilon;
+            minus[i] -= epsilon;
+            let numeric = (objective(&plus) - objective(&minus)) / (2.0 * epsilon);
+            assert!((numeric - analytic[i]).abs() < 1e-6, "index {}", i);
+        }
+    }
+
+    #[test]
+    fn test_fused_gradient_matches_chain_rule() {
+        let x = [0.5, -1.0, 2.0];
+        let target = [0.0, 1.0, 0.0];
+        for (activation, loss) in [(OutputActivation::Softmax, Loss::CrossEntropy), (OutputActivation::Sigmoid, Loss::BinaryCrossEntropy)] {
+            let y = activation.apply(&x);
+            let chained = activation.backward(&y, &loss.gradient(&y, &target));
+            let fused = activation.fused_gradient(loss, &y, &target, 3).unwrap();
+            for (c, f) in chained.iter().zip(fused.iter()) {
+                assert!((c - f).abs() < 1e-9, "{:?}", activation);
+            }
+        }
+        assert!(OutputActivation::Softmax.fused_gradient(Loss::Mse, &x, &target, 3).is_none());
+    }
+
+    #[test]
+    fn test_relu_has_zero_gradient_at_zero() {
+        assert_eq!(Activation::ReLU.derivative(Activation::ReLU.apply(0.0)), 0.0);
+        assert_eq!(Activation::ReLU.derivative(Activation::ReLU.apply(-3.0)), 0.0);
+    }
+}
@@ -1,111 +1,737 @@
+use std::cmp::Ordering;
+
+use ndarray::{Array1, Array2, Axis};
 use rand::Rng;
-use std::collections::VecDeque;
 
+use crate::activation::Activation;
+use crate::init::WeightInit;
+use crate::optimizer::{clip_grad_norm, default_optimizer, matrix_state_sources, Optimizer};
+use crate::ring_buffer::RingBuffer;
+
+/// Largest perturbation applied to each weight when a neuron is split during growth.
+const SPLIT_NOISE: f64 = 0.01;
+
+/// Random weight mutation applied by [`AdaptiveLayer::adapt`]: each neuron is
+/// picked with probability `rate`, and each weight of a picked neuron moves by
+/// a uniform amount in `(-strength, strength)` with probability `weight_probability`.
+/// The `elitism` most important neurons are never mutated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mutation {
+    pub rate: f64,
+    pub weight_probability: f64,
+    pub strength: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub elitism: usize,
+}
+
+impl Default for Mutation {
+    fn default() -> Self {
+        Mutation { rate: 0.1, weight_probability: 0.1, strength: 0.1, elitism: 0 }
+    }
+}
+
+/// A layer whose neuron count grows and shrinks with the emotional state.
+///
+/// The layer grows while the emotional state is above `grow_threshold` and
+/// shrinks while it is below `shrink_threshold`; in between, its size holds.
+/// A cooldown can further limit how often the size changes.
+///
+/// Row `n` of `weights` and entry `n` of every per-neuron vector describe the
+/// same neuron; [`AdaptiveLayer::adapt`] keeps them in step when it reorders,
+/// adds or removes neurons.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdaptiveLayer {
-    neurons: Vec<AdaptiveNeuron>,
+    // (neurons, inputs)
+    weights: Array2<f64>,
+    // Stays zero when `use_bias` is off.
+    bias: Array1<f64>,
+    use_bias: bool,
+    activation_history: Vec<RingBuffer<f64>>,
+    history_length: usize,
+    importance_scores: Array1<f64>,
     max_neurons: usize,
     min_neurons: usize,
-    adaptation_threshold: f64,
-}
-
-struct AdaptiveNeuron {
-    weights: Vec<f64>,
-    activation_history: VecDeque<f64>,
-    importance_score: f64,
+    grow_threshold: f64,
+    shrink_threshold: f64,
+    // Minimum number of `adapt` calls between two size changes, and how many
+    // calls remain before the next one is allowed.
+    cooldown: usize,
+    cooldown_remaining: usize,
+    mutation: Mutation,
+    activation: Activation,
+    dropout_rate: f64,
+    training: bool,
+    resizable: bool,
+    // Per-neuron output scale chosen by the last forward pass: 0 for dropped
+    // neurons, 1 / (1 - dropout_rate) for survivors. Empty when no dropout ran.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dropout_mask: Vec<f64>,
+    // Input of the last forward pass, which the weight gradients are taken against.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_input: Array1<f64>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_optimizer"))]
+    optimizer: Box<dyn Optimizer>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gradient_sum: Array2<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bias_gradient_sum: Array1<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    accumulated_steps: usize,
 }
 
 impl AdaptiveLayer {
-    pub fn new(initial_neurons: usize, max_neurons: usize, min_neurons: usize, adaptation_threshold: f64) -> Self {
+    /// Panics if `shrink_threshold` is above `grow_threshold`. Equal thresholds
+    /// leave no dead zone.
+    pub fn new(
+        initial_neurons: usize,
+        max_neurons: usize,
+        min_neurons: usize,
+        grow_threshold: f64,
+        shrink_threshold: f64,
+        activation: Activation,
+        history_length: usize,
+    ) -> Self {
+        Self::with_rng(initial_neurons, max_neurons, min_neurons, grow_threshold, shrink_threshold, activation, history_length, &mut rand::thread_rng())
+    }
+
+    /// `history_length` is the number of past activations each neuron keeps for
+    /// importance scoring, at least 1.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_rng<R: Rng + ?Sized>(
+        initial_neurons: usize,
+        max_neurons: usize,
+        min_neurons: usize,
+        grow_threshold: f64,
+        shrink_threshold: f64,
+        activation: Activation,
+        history_length: usize,
+        rng: &mut R,
+    ) -> Self {
+        Self::with_init(initial_neurons, max_neurons, min_neurons, grow_threshold, shrink_threshold, activation, WeightInit::default(), history_length, rng)
+    }
+
+    /// Like [`AdaptiveLayer::with_rng`], drawing the initial weights from `init`.
+    /// `min_neurons` is raised to 1, so adaptation never empties the layer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_init<R: Rng + ?Sized>(
+        initial_neurons: usize,
+        max_neurons: usize,
+        min_neurons: usize,
+        grow_threshold: f64,
+        shrink_threshold: f64,
+        activation: Activation,
+        init: WeightInit,
+        history_length: usize,
+        rng: &mut R,
+    ) -> Self {
+        assert!(shrink_threshold <= grow_threshold, "shrink_threshold must not be above grow_threshold");
+        let history_length = history_length.max(1);
+        let min_neurons = min_neurons.max(1);
         AdaptiveLayer {
-            neurons: (0..initial_neurons).map(|_| AdaptiveNeuron::new(initial_neurons)).collect(),
+            weights: Array2::from_shape_simple_fn((initial_neurons, initial_neurons), || init.sample(initial_neurons, initial_neurons, rng)),
+            bias: Array1::zeros(initial_neurons),
+            use_bias: true,
+            activation_history: vec![RingBuffer::new(history_length); initial_neurons],
+            history_length,
+            importance_scores: Array1::zeros(initial_neurons),
             max_neurons,
             min_neurons,
-            adaptation_threshold,
+            grow_threshold,
+            shrink_threshold,
+            cooldown: 0,
+            cooldown_remaining: 0,
+            mutation: Mutation::default(),
+            activation,
+            dropout_rate: 0.0,
+            training: true,
+            resizable: true,
+            dropout_mask: Vec::new(),
+            last_input: Array1::zeros(initial_neurons),
+            optimizer: default_optimizer(),
+            gradient_sum: Array2::zeros((0, 0)),
+            bias_gradient_sum: Array1::zeros(0),
+            accumulated_steps: 0,
+        }
+    }
+
+    /// Replaces the optimizer used to apply this layer's weight updates.
+    pub fn set_optimizer(&mut self, optimizer: Box<dyn Optimizer>) {
+        self.optimizer = optimizer;
+    }
+
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.optimizer.set_learning_rate(learning_rate);
+    }
+
+    pub fn learning_rate(&self) -> f64 {
+        self.optimizer.learning_rate()
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.weights.ncols()
+    }
+
+    /// Current number of neurons, which is also the length of the output.
+    pub fn output_size(&self) -> usize {
+        self.weights.nrows()
+    }
+
+    /// Current number of neurons; the same as [`AdaptiveLayer::output_size`].
+    pub fn neuron_count(&self) -> usize {
+        self.output_size()
+    }
+
+    /// Importance of each neuron as scored by the last [`AdaptiveLayer::adapt`],
+    /// in neuron order. All zero before the first adaptation.
+    pub fn importance_scores(&self) -> Vec<f64> {
+        self.importance_scores.to_vec()
+    }
+
+    /// Enables or disables the learnable per-neuron bias. Disabling it resets
+    /// every bias to zero.
+    pub fn set_use_bias(&mut self, use_bias: bool) {
+        self.use_bias = use_bias;
+        if !use_bias {
+            self.bias.fill(0.0);
+        }
+    }
+
+    /// Sets the probability of zeroing each neuron's output during training.
+    pub fn set_dropout_rate(&mut self, dropout_rate: f64) {
+        assert!((0.0..1.0).contains(&dropout_rate), "dropout_rate must be in [0, 1)");
+        self.dropout_rate = dropout_rate;
+    }
+
+    /// Switches between training mode (dropout active) and inference mode
+    /// (all outputs passed through unscaled).
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    /// Allows or forbids [`AdaptiveLayer::adapt`] from adding and removing
+    /// neurons. A layer feeding another layer must keep a fixed width, since the
+    /// next layer's input size does not follow it.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.resizable = resizable;
+    }
+
+    pub fn is_resizable(&self) -> bool {
+        self.resizable
+    }
+
+    /// Clears every neuron's activation history, so importance scores start
+    /// over from the next forward pass. The scores themselves are kept until then.
+    pub fn reset_history(&mut self) {
+        for history in &mut self.activation_history {
+            history.clear();
         }
     }
 
-    pub fn forward(&mut self, input: &[f64]) -> Vec<f64> {
-        self.neurons.iter_mut().map(|neuron| neuron.activate(input)).collect()
+    /// Makes [`AdaptiveLayer::adapt`] wait at least `cooldown` calls after a
+    /// size change before changing size again. 0 and 1 allow a change on every call.
+    pub fn set_cooldown(&mut self, cooldown: usize) {
+        self.cooldown = cooldown;
+        self.cooldown_remaining = self.cooldown_remaining.min(cooldown.saturating_sub(1));
+    }
+
+    /// Replaces the random mutation [`AdaptiveLayer::adapt`] applies. A zero
+    /// rate or strength disables it.
+    pub fn set_mutation(&mut self, mutation: Mutation) {
+        self.mutation = mutation;
     }
 
-    pub fn backward(&mut self, error: &[f64], learning_rate: f64) -> Vec<f64> {
-        let mut next_error = vec![0.0; self.neurons[0].weights.len()];
-        for (neuron, &neuron_error) in self.neurons.iter_mut().zip(error.iter()) {
-            let gradients = neuron.calculate_gradients(neuron_error);
-            neuron.update_weights(&gradients, learning_rate);
-            for (i, &gradient) in gradients.iter().enumerate() {
-                next_error[i] += gradient;
+    pub fn mutation(&self) -> Mutation {
+        self.mutation
+    }
+
+    pub fn grow_threshold(&self) -> f64 {
+        self.grow_threshold
+    }
+
+    pub fn shrink_threshold(&self) -> f64 {
+        self.shrink_threshold
+    }
+
+    pub fn forward<R: Rng + ?Sized>(&mut self, input: &[f64], rng: &mut R) -> Vec<f64> {
+        self.last_input = Array1::from_vec(input.to_vec());
+        let weighted_sums = self.weights.dot(&self.last_input) + &self.bias;
+        let mut output: Vec<f64> = weighted_sums.iter().map(|&x| self.activation.apply(x)).collect();
+
+        for (history, &activation) in self.activation_history.iter_mut().zip(output.iter()) {
+            history.push(activation);
+        }
+
+        self.dropout_mask.clear();
+        if self.training && self.dropout_rate > 0.0 {
+            let keep_scale = 1.0 / (1.0 - self.dropout_rate);
+            for value in &mut output {
+                let scale = if rng.gen::<f64>() < self.dropout_rate { 0.0 } else { keep_scale };
+                *value *= scale;
+                self.dropout_mask.push(scale);
             }
         }
+
+        output
+    }
+
+    /// Outputs of [`AdaptiveLayer::forward`] in evaluation mode, without
+    /// recording any history.
+    pub fn infer(&self, input: &[f64]) -> Vec<f64> {
+        let weighted_sums = self.weights.dot(&ndarray::aview1(input)) + &self.bias;
+        weighted_sums.iter().map(|&x| self.activation.apply(x)).collect()
+    }
+
+    /// Weights, biases and activation function, as used by [`AdaptiveLayer::infer`].
+    pub(crate) fn parameters(&self) -> (&Array2<f64>, &Array1<f64>, Activation) {
+        (&self.weights, &self.bias, self.activation)
+    }
+
+    /// Backpropagates `error` and immediately applies the resulting update.
+    pub fn backward(&mut self, error: &[f64]) -> Vec<f64> {
+        let next_error = self.accumulate_gradients(error);
+        self.apply_gradients(0.0, None);
         next_error
     }
 
-    pub fn adapt(&mut self, emotional_state: f64) {
-        let mut rng = rand::thread_rng();
+    /// Backpropagates `error`, adding the weight gradients to the layer's running
+    /// sum instead of applying them. Call [`AdaptiveLayer::apply_gradients`] to update.
+    pub fn accumulate_gradients(&mut self, error: &[f64]) -> Vec<f64> {
+        if self.gradient_sum.dim() != self.weights.dim() {
+            self.gradient_sum = Array2::zeros(self.weights.dim());
+            self.bias_gradient_sum = Array1::zeros(self.bias.len());
+        }
 
-        for neuron in &mut self.neurons {
-            neuron.update_importance(emotional_state);
+        // Error at each neuron's pre-activation; neurons beyond `error` get none.
+        let delta = Array1::from_shape_fn(self.output_size(), |n| {
+            let scale = self.dropout_mask.get(n).copied().unwrap_or(1.0);
+            let last_activation = *self.activation_history[n].back().expect("accumulate_gradients needs a forward pass first");
+            error.get(n).copied().unwrap_or(0.0) * scale * self.activation.derivative(last_activation)
+        });
+
+        let gradients = delta.view().insert_axis(Axis(1)).dot(&self.last_input.view().insert_axis(Axis(0)));
+        let next_error = self.weights.t().dot(&delta);
+        self.gradient_sum += &gradients;
+        if self.use_bias {
+            self.bias_gradient_sum += &delta;
         }
 
-        self.neurons.sort_by(|a, b| b.importance_score.partial_cmp(&a.importance_score).unwrap());
+        self.accumulated_steps += 1;
+        next_error.to_vec()
+    }
+
+    /// Applies one optimizer step with the gradients accumulated since the last
+    /// call, averaged over the number of accumulated examples. The averaged
+    /// gradient is clipped to an L2 norm of `max_grad_norm` if given, then an L2
+    /// weight decay term of `l2_lambda * weight` is added.
+    pub fn apply_gradients(&mut self, l2_lambda: f64, max_grad_norm: Option<f64>) {
+        if self.accumulated_steps == 0 || self.weights.is_empty() {
+            return;
+        }
 
-        if emotional_state > self.adaptation_threshold && self.neurons.len() < self.max_neurons {
-            self.neurons.push(AdaptiveNeuron::new(self.neurons[0].weights.len()));
-        } else if emotional_state < self.adaptation_threshold && self.neurons.len() > self.min_neurons {
-            self.neurons.pop();
+        // Weights and biases go through the optimizer as one parameter vector,
+        // so that stateful optimizers see a consistent length.
+        let scale = 1.0 / self.accumulated_steps as f64;
+        let mut gradients: Vec<f64> = self.gradient_sum.iter().chain(self.bias_gradient_sum.iter()).map(|&g| g * scale).collect();
+        if let Some(max_norm) = max_grad_norm {
+            clip_grad_norm(&mut gradients, max_norm);
         }
+        let mut parameters: Vec<f64> = self.weights.iter().chain(self.bias.iter()).copied().collect();
+        for (gradient, &weight) in gradients.iter_mut().zip(self.weights.iter()) {
+            *gradient += l2_lambda * weight;
+        }
+        self.optimizer.step(&mut parameters, &gradients);
+
+        let (weights, bias) = parameters.split_at(self.weights.len());
+        self.weights.iter_mut().zip(weights).for_each(|(w, &updated)| *w = updated);
+        self.bias.iter_mut().zip(bias).for_each(|(b, &updated)| *b = updated);
+
+        self.clear_gradients();
+    }
 
-        for neuron in &mut self.neurons {
-            if rng.gen::<f64>() < 0.1 {
-                neuron.mutate();
+    /// Discards the gradients accumulated since the last update without applying them.
+    pub fn clear_gradients(&mut self) {
+        self.gradient_sum.fill(0.0);
+        self.bias_gradient_sum.fill(0.0);
+        self.accumulated_steps = 0;
+    }
+
+    /// Sum of squared weights, used for the L2 penalty.
+    pub fn squared_weight_sum(&self) -> f64 {
+        self.weights.iter().map(|w| w * w).sum()
+    }
+
+    /// Mutable access to the weight at row-major `index`, for gradient checking.
+    pub(crate) fn weight_mut(&mut self, index: usize) -> &mut f64 {
+        let inputs = self.input_size();
+        &mut self.weights[[index / inputs, index % inputs]]
+    }
+
+    /// Accumulated gradient of the weight at row-major `index`.
+    pub(crate) fn weight_gradient(&self, index: usize) -> f64 {
+        self.gradient_sum.iter().nth(index).copied().unwrap_or(0.0)
+    }
+
+    /// Describes every weight or bias holding NaN or an infinity, as
+    /// `neuron N weight I` or `neuron N bias`.
+    pub fn non_finite_parameters(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for (n, row) in self.weights.outer_iter().enumerate() {
+            for (i, weight) in row.iter().enumerate() {
+                if !weight.is_finite() {
+                    found.push(format!("neuron {} weight {}", n, i));
+                }
+            }
+            if !self.bias[n].is_finite() {
+                found.push(format!("neuron {} bias", n));
             }
         }
+        found
+    }
+
+    /// Re-ranks neurons by importance, grows or shrinks the layer by one neuron
+    /// depending on `emotional_state`, and randomly mutates some neurons. Growth
+    /// and shrinking are skipped unless the layer is resizable, so its output
+    /// size only changes when nothing downstream depends on it, and while the
+    /// cooldown since the last size change is running.
+    pub fn adapt<R: Rng + ?Sized>(&mut self, emotional_state: f64, rng: &mut R) {
+        self.score_importance(emotional_state);
+        let mut order: Vec<usize> = (0..self.output_size()).collect();
+        order.sort_by(|&a, &b| by_descending_importance(self.importance_scores[a], self.importance_scores[b]));
+        self.reorder(&order);
+
+        let cooling_down = self.cooldown_remaining > 0;
+        self.cooldown_remaining = self.cooldown_remaining.saturating_sub(1);
+        let size_before = self.output_size();
+        if !self.resizable || cooling_down {
+            // Keep the width; only mutation below applies.
+        } else if emotional_state > self.grow_threshold && self.output_size() < self.max_neurons && self.output_size() > 0 {
+            // Split the most important neuron rather than adding a random one,
+            // so growth does not disturb what the layer has learned.
+            let child: Array1<f64> = self.weights.row(0).mapv(|w| w + rng.gen_range(-SPLIT_NOISE..SPLIT_NOISE));
+            let rows: Vec<Option<usize>> = (0..self.output_size()).map(Some).chain(std::iter::once(None)).collect();
+            self.remap_optimizer(&rows, &(0..self.input_size()).collect::<Vec<usize>>());
+            self.weights.push_row(child.view()).expect("child row matches the input size");
+            self.bias = self.bias.iter().copied().chain(std::iter::once(self.bias[0])).collect();
+            self.activation_history.push(RingBuffer::new(self.history_length));
+            self.importance_scores = self.importance_scores.iter().copied().chain(std::iter::once(0.0)).collect();
+        } else if emotional_state < self.shrink_threshold && self.output_size() > self.min_neurons {
+            let kept: Vec<usize> = (0..self.output_size() - 1).collect();
+            self.reorder(&kept);
+        }
+        if self.output_size() != size_before {
+            self.cooldown_remaining = self.cooldown.saturating_sub(1);
+        }
+
+        // Rows are sorted by importance, so the elite are the leading rows.
+        let Mutation { rate, weight_probability, strength, elitism } = self.mutation;
+        for mut row in self.weights.outer_iter_mut().skip(elitism) {
+            if rng.gen::<f64>() < rate {
+                for weight in row.iter_mut() {
+                    if rng.gen::<f64>() < weight_probability && strength > 0.0 {
+                        *weight += rng.gen_range(-strength..strength);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes every neuron whose importance, scored as in
+    /// [`AdaptiveLayer::adapt`], is below `importance_threshold`, but keeps the
+    /// most important ones if that would leave fewer than the minimum number of
+    /// neurons. The remaining neurons keep their order. Returns the indices of
+    /// the removed neurons, in ascending order; the layer's output shrinks by
+    /// as many values, whether or not it is resizable.
+    pub fn prune(&mut self, importance_threshold: f64, emotional_state: f64) -> Vec<usize> {
+        self.score_importance(emotional_state);
+        let mut order: Vec<usize> = (0..self.output_size()).collect();
+        order.sort_by(|&a, &b| by_descending_importance(self.importance_scores[a], self.importance_scores[b]));
+        // NaN scores fail the comparison and are pruned.
+        let above = order.iter().take_while(|&&n| self.importance_scores[n] >= importance_threshold).count();
+        let mut kept = order[..above.max(self.min_neurons).min(order.len())].to_vec();
+        kept.sort_unstable();
+        let removed: Vec<usize> = (0..self.output_size()).filter(|n| kept.binary_search(n).is_err()).collect();
+        if !removed.is_empty() {
+            self.reorder(&kept);
+            self.dropout_mask.clear();
+            self.clear_gradients();
+        }
+        removed
+    }
+
+    /// Keeps only the inputs listed in `kept`, in that order, after the layer
+    /// feeding this one lost neurons.
+    pub(crate) fn keep_inputs(&mut self, kept: &[usize]) {
+        let rows: Vec<Option<usize>> = (0..self.output_size()).map(Some).collect();
+        self.remap_optimizer(&rows, kept);
+        self.weights = self.weights.select(Axis(1), kept);
+        self.last_input = Array1::zeros(kept.len());
+        self.clear_gradients();
+    }
+
+    /// Moves the optimizer's per-weight state to follow a change of neurons
+    /// or inputs that is about to be made; see [`matrix_state_sources`].
+    fn remap_optimizer(&mut self, rows: &[Option<usize>], cols: &[usize]) {
+        self.optimizer.remap_state(&matrix_state_sources(self.weights.dim(), rows, cols));
+    }
+
+    fn score_importance(&mut self, emotional_state: f64) {
+        for (score, history) in self.importance_scores.iter_mut().zip(self.activation_history.iter()) {
+            // A neuron that has not fired yet, e.g. one just added by growth,
+            // has no evidence of importance.
+            let avg_activation = history.mean().unwrap_or(0.0);
+            *score = avg_activation * (1.0 - emotional_state);
+        }
+    }
+
+    /// Keeps only the neurons listed in `order`, in that order.
+    fn reorder(&mut self, order: &[usize]) {
+        let rows: Vec<Option<usize>> = order.iter().copied().map(Some).collect();
+        self.remap_optimizer(&rows, &(0..self.input_size()).collect::<Vec<usize>>());
+        self.weights = self.weights.select(Axis(0), order);
+        self.bias = self.bias.select(Axis(0), order);
+        self.importance_scores = self.importance_scores.select(Axis(0), order);
+        self.activation_history = order.iter().map(|&n| self.activation_history[n].clone()).collect();
+    }
+}
+
+/// Orders importance scores from most to least important. NaN ranks below
+/// every number, so a neuron whose activations went NaN is pruned first.
+fn by_descending_importance(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+        (a_nan, b_nan) => a_nan.cmp(&b_nan),
     }
 }
 
-impl AdaptiveNeuron {
-    fn new(input_size: usize) -> Self {
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_HISTORY_LENGTH;
+    use crate::optimizer::Adam;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn history_keeps_only_the_configured_length() {
         let mut rng = rand::thread_rng();
-        AdaptiveNeuron {
-            weights: (0..input_size).map(|_| rng.gen_range(-1.0..1.0)).collect(),
-            activation_history: VecDeque::with_capacity(100),
-            importance_score: 0.0,
+        let mut layer = AdaptiveLayer::with_rng(2, 4, 1, 0.5, 0.5, Activation::Sigmoid, 3, &mut rng);
+        for step in 0..5 {
+            layer.forward(&[step as f64, 0.0], &mut rng);
         }
+        assert!(layer.activation_history.iter().all(|history| history.len() == 3));
+    }
+
+    #[test]
+    fn test_empty_layer_runs_without_panicking() {
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        let mut layer = AdaptiveLayer::with_rng(0, 4, 0, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        assert!(layer.forward(&[], &mut rng).is_empty());
+        assert!(layer.backward(&[]).is_empty());
+        layer.adapt(1.0, &mut rng);
+        assert_eq!(layer.output_size(), 0);
     }
 
-    fn activate(&mut self, input: &[f64]) -> f64 {
-        let weighted_sum: f64 = input.iter().zip(self.weights.iter()).map(|(&x, &w)| x * w).sum();
-        let activation = 1.0 / (1.0 + (-weighted_sum).exp());
-        if self.activation_history.len() >= 100 {
-            self.activation_history.pop_front();
+    #[test]
+    fn test_shrinking_stops_at_one_neuron() {
+        let mut rng = ChaCha12Rng::seed_from_u64(8);
+        let mut layer = AdaptiveLayer::with_rng(2, 4, 0, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        for _ in 0..3 {
+            layer.forward(&vec![0.5; layer.input_size()], &mut rng);
+            layer.adapt(0.0, &mut rng);
         }
-        self.activation_history.push_back(activation);
-        activation
+        assert_eq!(layer.output_size(), 1);
     }
 
-    fn calculate_gradients(&self, error: f64) -> Vec<f64> {
-        let last_activation = *self.activation_history.back().unwrap();
-        let gradient = error * last_activation * (1.0 - last_activation);
-        self.weights.iter().map(|&w| gradient * w).collect()
+    #[test]
+    fn test_dead_zone_and_cooldown_hold_the_size() {
+        let mut rng = ChaCha12Rng::seed_from_u64(9);
+        let mut layer = AdaptiveLayer::with_rng(3, 8, 1, 0.7, 0.3, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.forward(&[0.5; 3], &mut rng);
+        layer.adapt(0.5, &mut rng);
+        assert_eq!(layer.output_size(), 3);
+
+        layer.set_cooldown(3);
+        let sizes: Vec<usize> = (0..7)
+            .map(|_| {
+                layer.forward(&[0.5; 3], &mut rng);
+                layer.adapt(0.9, &mut rng);
+                layer.output_size()
+            })
+            .collect();
+        assert_eq!(sizes, vec![4, 4, 4, 5, 5, 5, 6]);
+    }
+
+    #[test]
+    fn test_mutation_follows_its_settings() {
+        let mut rng = ChaCha12Rng::seed_from_u64(10);
+        let mut layer = AdaptiveLayer::with_rng(4, 4, 4, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.forward(&[0.5; 4], &mut rng);
+        layer.adapt(0.5, &mut rng);
+        let settled = layer.weights.clone();
+
+        layer.set_mutation(Mutation { rate: 0.0, ..Mutation::default() });
+        layer.adapt(0.5, &mut rng);
+        assert_eq!(layer.weights, settled);
+
+        layer.set_mutation(Mutation { rate: 1.0, weight_probability: 1.0, strength: 0.5, elitism: 0 });
+        layer.adapt(0.5, &mut rng);
+        let changes: Vec<f64> = layer.weights.iter().zip(settled.iter()).map(|(a, b)| (a - b).abs()).collect();
+        assert!(changes.iter().all(|&change| change > 0.0 && change < 0.5));
     }
 
-    fn update_weights(&mut self, gradients: &[f64], learning_rate: f64) {
-        for (weight, &gradient) in self.weights.iter_mut().zip(gradients.iter()) {
-            *weight -= learning_rate * gradient;
+    #[test]
+    fn test_elite_neurons_are_not_mutated() {
+        let mut rng = ChaCha12Rng::seed_from_u64(11);
+        let mut layer = AdaptiveLayer::with_rng(5, 5, 5, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_mutation(Mutation { rate: 1.0, weight_probability: 1.0, strength: 0.5, elitism: 2 });
+        // With one forward pass, importance ranks neurons by their output.
+        let outputs = layer.forward(&[1.0, -0.5, 0.25, 0.75, -1.0], &mut rng);
+        let mut order: Vec<usize> = (0..5).collect();
+        order.sort_by(|&a, &b| outputs[b].partial_cmp(&outputs[a]).unwrap());
+        let ranked = layer.weights.select(Axis(0), &order);
+
+        layer.adapt(0.5, &mut rng);
+        for row in 0..5 {
+            let unchanged = layer.weights.row(row) == ranked.row(row);
+            assert_eq!(unchanged, row < 2, "row {row}");
         }
     }
 
-    fn update_importance(&mut self, emotional_state: f64) {
-        let avg_activation = self.activation_history.iter().sum::<f64>() / self.activation_history.len() as f64;
-        self.importance_score = avg_activation * (1.0 - emotional_state);
+    #[test]
+    fn test_dropout_masks_forward_and_backward() {
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let mut layer = AdaptiveLayer::with_rng(8, 16, 4, 0.1, 0.1, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_dropout_rate(0.5);
+
+        let input = vec![0.5; 8];
+        let output = layer.forward(&input, &mut rng);
+        let dropped: Vec<usize> = (0..8).filter(|&i| output[i] == 0.0).collect();
+        assert!(!dropped.is_empty() && dropped.len() < 8);
+
+        let weights_before = layer.weights.clone();
+        layer.backward(&[1.0; 8]);
+        for &i in &dropped {
+            assert_eq!(layer.weights.row(i), weights_before.row(i));
+        }
     }
 
-    fn mutate(&mut self) {
-        let mut rng = rand::thread_rng();
-        for weight in &mut self.weights {
-            if rng.gen::<f64>() < 0.1 {
-                *weight += rng.gen_range(-0.1..0.1);
+    #[test]
+    fn test_growth_splits_most_important_neuron() {
+        let mut rng = ChaCha12Rng::seed_from_u64(3);
+        let mut layer = AdaptiveLayer::with_rng(3, 6, 1, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.forward(&[0.4, -0.2, 0.9], &mut rng);
+
+        layer.adapt(0.9, &mut rng);
+        assert_eq!(layer.output_size(), 4);
+        assert!(layer.activation_history[3].is_empty());
+        assert_eq!(layer.importance_scores[3], 0.0);
+        // Allow for the random mutation that may follow growth.
+        for (&w, &parent_w) in layer.weights.row(3).iter().zip(layer.weights.row(0).iter()) {
+            assert!((w - parent_w).abs() < SPLIT_NOISE + 0.2);
+        }
+    }
+
+    #[test]
+    fn test_adapt_right_after_growth_scores_the_new_neuron_zero() {
+        let mut rng = ChaCha12Rng::seed_from_u64(12);
+        let mut layer = AdaptiveLayer::with_rng(3, 6, 1, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_mutation(Mutation { rate: 0.0, ..Mutation::default() });
+        layer.forward(&[0.4, -0.2, 0.9], &mut rng);
+        layer.adapt(0.9, &mut rng);
+        assert_eq!(layer.output_size(), 4);
+
+        layer.adapt(0.9, &mut rng);
+        assert_eq!(layer.output_size(), 5);
+        assert!(layer.importance_scores().iter().all(|score| score.is_finite()));
+        // Neither neuron added by growth has fired, so both rank last with no importance.
+        assert_eq!(layer.importance_scores()[3], 0.0);
+        assert_eq!(layer.importance_scores()[4], 0.0);
+    }
+
+    #[test]
+    fn test_shrink_removes_least_important_neuron() {
+        let mut rng = ChaCha12Rng::seed_from_u64(4);
+        let mut layer = AdaptiveLayer::with_rng(3, 6, 1, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        let mut activations = layer.forward(&[0.4, -0.2, 0.9], &mut rng);
+
+        layer.adapt(0.1, &mut rng);
+        activations.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        let kept: Vec<f64> = layer.activation_history.iter().map(|history| *history.back().unwrap()).collect();
+        assert_eq!(kept, activations[..2]);
+    }
+
+    #[test]
+    fn test_nan_importance_ranks_last_and_is_pruned_first() {
+        let mut rng = ChaCha12Rng::seed_from_u64(13);
+        let mut layer = AdaptiveLayer::with_rng(3, 6, 1, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_mutation(Mutation { rate: 0.0, ..Mutation::default() });
+        layer.weights.row_mut(0).fill(f64::NAN);
+        layer.forward(&[0.4, -0.2, 0.9], &mut rng);
+
+        layer.adapt(0.5, &mut rng);
+        assert!(layer.importance_scores()[2].is_nan());
+        assert!(layer.weights.row(2).iter().all(|w| w.is_nan()));
+
+        layer.adapt(0.1, &mut rng);
+        assert_eq!(layer.output_size(), 2);
+        assert!(layer.weights.iter().all(|w| w.is_finite()));
+    }
+
+    #[test]
+    fn test_prune_removes_unimportant_neurons_down_to_the_minimum() {
+        let mut rng = ChaCha12Rng::seed_from_u64(14);
+        for (min_neurons, expected) in [(2, vec![1, 3]), (3, vec![1])] {
+            let mut layer = AdaptiveLayer::with_rng(4, 4, min_neurons, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+            layer.weights.row_mut(1).fill(-10.0);
+            layer.weights.row_mut(3).fill(-5.0);
+            layer.forward(&[1.0; 4], &mut rng);
+            let survivors = layer.weights.select(Axis(0), &[0, 2]);
+
+            assert_eq!(layer.prune(0.01, 0.5), expected);
+            assert_eq!(layer.output_size(), 4 - expected.len());
+            assert_eq!(layer.weights.row(0), survivors.row(0));
+            assert_eq!(layer.weights.row(1), survivors.row(1));
+        }
+    }
+
+    #[test]
+    fn test_optimizer_state_follows_reordered_neurons() {
+        let mut rng = ChaCha12Rng::seed_from_u64(15);
+        let mut layer = AdaptiveLayer::with_rng(3, 3, 1, 0.5, 0.5, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_optimizer(Box::new(Adam::new(0.05)));
+        let input = [0.4, -0.2, 0.9];
+        let error = [0.3, -0.6, 0.1];
+        for _ in 0..3 {
+            layer.forward(&input, &mut rng);
+            layer.backward(&error);
+        }
+
+        let order = [2, 0, 1];
+        let mut reordered = layer.clone();
+        reordered.reorder(&order);
+        layer.forward(&input, &mut rng);
+        layer.backward(&error);
+        reordered.forward(&input, &mut rng);
+        reordered.backward(&order.map(|n| error[n]));
+        for (row, &n) in order.iter().enumerate() {
+            for (a, b) in reordered.weights.row(row).iter().zip(layer.weights.row(n).iter()) {
+                assert!((a - b).abs() < 1e-12);
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_eval_mode_passes_through_unscaled() {
+        let mut rng = ChaCha12Rng::seed_from_u64(2);
+        let mut layer = AdaptiveLayer::with_rng(4, 8, 2, 0.1, 0.1, Activation::Sigmoid, DEFAULT_HISTORY_LENGTH, &mut rng);
+        layer.set_dropout_rate(0.5);
+        layer.set_training(false);
+
+        let input = vec![0.3; 4];
+        let output = layer.forward(&input, &mut rng);
+        let expected: Vec<f64> = layer.activation_history.iter().map(|history| *history.back().unwrap()).collect();
+        assert_eq!(output, expected);
+    }
+}
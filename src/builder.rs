@@ -0,0 +1,491 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::activation::{Activation, OutputActivation};
+use crate::adaptive_architecture::{AdaptiveLayer, Mutation};
+use crate::config::{EmotionalConfig, DEFAULT_HISTORY_LENGTH};
+use crate::error::BuildError;
+use crate::emotional_memory::{EmotionalMemory, EvictionPolicy};
+use crate::init::WeightInit;
+use crate::neuro_symbolic::NeuroSymbolicLayer;
+use crate::quantum_neuron::QuantumMode;
+use crate::temporal_plasticity::{TemporalKernel, TemporalLayer, DEFAULT_DELAY_RANGE};
+use crate::{Layer, NeuroForge, QuantumLayer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LayerSpec {
+    Quantum(usize),
+    Adaptive { size: usize, max_neurons: usize, min_neurons: usize, grow_threshold: f64, shrink_threshold: f64 },
+    Temporal(usize),
+}
+
+/// Step-by-step construction of a [`NeuroForge`]. Layers run in the order
+/// they are added; every other setting applies to the whole network.
+#[derive(Debug, Clone)]
+pub struct NeuroForgeBuilder {
+    layers: Vec<LayerSpec>,
+    emotional_capacity: usize,
+    eviction_policy: EvictionPolicy,
+    emotional_config: EmotionalConfig,
+    seed: Option<u64>,
+    kernel: TemporalKernel,
+    quantum_mode: QuantumMode,
+    unit_quantum_output: bool,
+    use_bias: bool,
+    init: WeightInit,
+    output_activation: OutputActivation,
+    history_length: usize,
+    adaptation_cooldown: usize,
+    adaptive_activation: Activation,
+    mutation: Mutation,
+    plasticity: Option<f64>,
+    learn_plasticity: bool,
+    refractory: Option<(f64, f64)>,
+    delay_range: (f64, f64),
+}
+
+impl Default for NeuroForgeBuilder {
+    fn default() -> Self {
+        NeuroForgeBuilder {
+            layers: Vec::new(),
+            emotional_capacity: 100,
+            eviction_policy: EvictionPolicy::default(),
+            emotional_config: EmotionalConfig::default(),
+            seed: None,
+            kernel: TemporalKernel::default(),
+            quantum_mode: QuantumMode::default(),
+            unit_quantum_output: false,
+            use_bias: true,
+            init: WeightInit::default(),
+            output_activation: OutputActivation::default(),
+            history_length: DEFAULT_HISTORY_LENGTH,
+            adaptation_cooldown: 0,
+            adaptive_activation: Activation::default(),
+            mutation: Mutation::default(),
+            plasticity: None,
+            learn_plasticity: false,
+            refractory: None,
+            delay_range: DEFAULT_DELAY_RANGE,
+        }
+    }
+}
+
+impl NeuroForgeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder for the parallel-array layout taken by [`NeuroForge::new`]:
+    /// layer `i` is adaptive if `adaptive_layers[i]`, otherwise temporal if
+    /// `temporal_layers[i]`, otherwise quantum. Layers beyond the shortest
+    /// slice are dropped.
+    pub(crate) fn from_flags(layer_sizes: &[usize], adaptive_layers: &[bool], temporal_layers: &[bool]) -> Self {
+        let mut builder = Self::new();
+        for (&size, (&is_adaptive, &is_temporal)) in layer_sizes.iter().zip(adaptive_layers.iter().zip(temporal_layers.iter())) {
+            builder = if is_adaptive {
+                builder.adaptive_layer(size, size * 2, size / 2, 0.1)
+            } else if is_temporal {
+                builder.temporal_layer(size)
+            } else {
+                builder.quantum_layer(size)
+            };
+        }
+        builder
+    }
+
+    /// Adds a quantum layer of `size` neurons. It accepts as many inputs as the
+    /// previous layer produces, or `size` inputs when it is the first layer.
+    pub fn quantum_layer(mut self, size: usize) -> Self {
+        self.layers.push(LayerSpec::Quantum(size));
+        self
+    }
+
+    /// Adds an adaptive layer starting at `size` neurons. Only the last layer
+    /// of the network grows and shrinks, between `min_neurons` and `max_neurons`.
+    pub fn adaptive_layer(self, size: usize, max_neurons: usize, min_neurons: usize, adaptation_threshold: f64) -> Self {
+        self.adaptive_layer_with_thresholds(size, max_neurons, min_neurons, adaptation_threshold, adaptation_threshold)
+    }
+
+    /// Like [`NeuroForgeBuilder::adaptive_layer`], growing above `grow_threshold`
+    /// and shrinking below `shrink_threshold`, with no change in between.
+    pub fn adaptive_layer_with_thresholds(
+        mut self,
+        size: usize,
+        max_neurons: usize,
+        min_neurons: usize,
+        grow_threshold: f64,
+        shrink_threshold: f64,
+    ) -> Self {
+        self.layers.push(LayerSpec::Adaptive { size, max_neurons, min_neurons, grow_threshold, shrink_threshold });
+        self
+    }
+
+    /// Minimum number of adaptation steps between two size changes of an
+    /// adaptive layer; see [`AdaptiveLayer::set_cooldown`]. Defaults to 0.
+    pub fn adaptation_cooldown(mut self, cooldown: usize) -> Self {
+        self.adaptation_cooldown = cooldown;
+        self
+    }
+
+    /// Activation function of every adaptive layer's neurons. Defaults to
+    /// [`Activation::Sigmoid`].
+    pub fn adaptive_activation(mut self, activation: Activation) -> Self {
+        self.adaptive_activation = activation;
+        self
+    }
+
+    /// Adds a temporal layer of `size` neurons. Like a quantum layer, it accepts
+    /// as many inputs as the previous layer produces, or `size` when first.
+    pub fn temporal_layer(mut self, size: usize) -> Self {
+        self.layers.push(LayerSpec::Temporal(size));
+        self
+    }
+
+    /// Number of outputs the emotional memory keeps. Defaults to 100.
+    pub fn emotional_capacity(mut self, capacity: usize) -> Self {
+        self.emotional_capacity = capacity;
+        self
+    }
+
+    /// Which stored output the emotional memory drops once it is full.
+    pub fn eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction_policy = policy;
+        self
+    }
+
+    /// Random weight mutation applied by every adaptive layer; see [`Mutation`].
+    pub fn mutation(mut self, mutation: Mutation) -> Self {
+        self.mutation = mutation;
+        self
+    }
+
+    /// Initial plasticity of every temporal neuron, instead of a random value
+    /// in `0.0..0.1`; see [`TemporalNeuron::set_plasticity`](crate::temporal_plasticity::TemporalNeuron::set_plasticity).
+    pub fn plasticity(mut self, plasticity: f64) -> Self {
+        self.plasticity = Some(plasticity);
+        self
+    }
+
+    /// Lets temporal neurons learn their plasticity during training; see
+    /// [`TemporalNeuron::update_delays`](crate::temporal_plasticity::TemporalNeuron::update_delays). Off by default.
+    pub fn learn_plasticity(mut self, enabled: bool) -> Self {
+        self.learn_plasticity = enabled;
+        self
+    }
+
+    /// Gives every temporal neuron a refractory period after activations above
+    /// `fire_threshold`; see [`TemporalNeuron::set_refractory`](crate::temporal_plasticity::TemporalNeuron::set_refractory).
+    pub fn refractory(mut self, refractory_period: f64, fire_threshold: f64) -> Self {
+        self.refractory = Some((refractory_period, fire_threshold));
+        self
+    }
+
+    /// Range every temporal neuron draws its initial delays from and keeps
+    /// them within, in the same time units as the timestamps passed to
+    /// `forward`; see [`TemporalNeuron::with_delay_range`](crate::temporal_plasticity::TemporalNeuron::with_delay_range).
+    /// Defaults to `0.0` to `1.0`. [`NeuroForgeBuilder::build`] rejects a range
+    /// that is not finite or whose `min_delay` is above `max_delay`.
+    pub fn delay_range(mut self, min_delay: f64, max_delay: f64) -> Self {
+        self.delay_range = (min_delay, max_delay);
+        self
+    }
+
+    /// How the emotional state follows the training error.
+    /// [`NeuroForgeBuilder::build`] rejects a `clamp` with a NaN bound or
+    /// whose minimum is above its maximum.
+    pub fn emotional_config(mut self, config: EmotionalConfig) -> Self {
+        self.emotional_config = config;
+        self
+    }
+
+    /// Seeds the network's random generator, making initialization and
+    /// stochastic behaviour reproducible. Without a seed the generator is
+    /// seeded from the operating system.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Kernel used by every temporal layer.
+    pub fn temporal_kernel(mut self, kernel: TemporalKernel) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    /// Mode used by every quantum neuron.
+    pub fn quantum_mode(mut self, mode: QuantumMode) -> Self {
+        self.quantum_mode = mode;
+        self
+    }
+
+    /// Whether quantum neurons rescale their superposed output into `[0, 1]`;
+    /// see [`QuantumNeuron`](crate::quantum_neuron::QuantumNeuron). Defaults to false.
+    pub fn unit_quantum_output(mut self, unit_output: bool) -> Self {
+        self.unit_quantum_output = unit_output;
+        self
+    }
+
+    /// Whether neurons have a learnable bias. Defaults to true.
+    pub fn use_bias(mut self, use_bias: bool) -> Self {
+        self.use_bias = use_bias;
+        self
+    }
+
+    pub fn weight_init(mut self, init: WeightInit) -> Self {
+        self.init = init;
+        self
+    }
+
+    /// Number of past activations each adaptive and temporal neuron keeps.
+    /// Defaults to [`DEFAULT_HISTORY_LENGTH`].
+    pub fn history_length(mut self, history_length: usize) -> Self {
+        self.history_length = history_length;
+        self
+    }
+
+    pub fn output_activation(mut self, activation: OutputActivation) -> Self {
+        self.output_activation = activation;
+        self
+    }
+
+    /// Builds the network, checking that every adaptive layer starts within
+    /// its neuron bounds, that the delay range and emotional clamp are valid,
+    /// and that every layer accepts as many inputs as the layer before it
+    /// produces.
+    pub fn build(self) -> Result<NeuroForge, BuildError> {
+        self.emotional_config.check()?;
+        let (min, max) = self.delay_range;
+        if !(min.is_finite() && max.is_finite() && min <= max) {
+            return Err(BuildError::InvalidDelayRange { min, max });
+        }
+        for (layer, spec) in self.layers.iter().enumerate() {
+            if let LayerSpec::Adaptive { size, max_neurons, min_neurons, grow_threshold, shrink_threshold } = *spec {
+                if min_neurons > max_neurons || size < min_neurons || size > max_neurons {
+                    return Err(BuildError::InvalidNeuronBounds { layer, initial: size, min: min_neurons, max: max_neurons });
+                }
+                if grow_threshold.is_nan() || shrink_threshold.is_nan() || shrink_threshold > grow_threshold {
+                    return Err(BuildError::InvalidAdaptationThresholds { layer, grow: grow_threshold, shrink: shrink_threshold });
+                }
+            }
+        }
+        let network = self.build_unchecked();
+        for (i, pair) in network.layers.windows(2).enumerate() {
+            let (got, expected) = (pair[0].output_size(), pair[1].input_size());
+            if got != expected {
+                return Err(BuildError::IncompatibleLayers { layer: i + 1, expected, got });
+            }
+        }
+        Ok(network)
+    }
+
+    /// Builds the network without checking layer sizes, as the array-based
+    /// constructors always have.
+    pub(crate) fn build_unchecked(self) -> NeuroForge {
+        let mut rng = match self.seed {
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_entropy(),
+        };
+
+        let last = self.layers.len().saturating_sub(1);
+        let mut layers: Vec<Layer> = Vec::with_capacity(self.layers.len());
+        for (i, spec) in self.layers.iter().enumerate() {
+            let layer = match *spec {
+                LayerSpec::Quantum(size) => {
+                    let in_size = layers.last().map_or(size, |previous| previous.output_size());
+                    Layer::Quantum(QuantumLayer::new(in_size, size, self.quantum_mode, self.unit_quantum_output, self.use_bias, self.init, &mut rng))
+                }
+                LayerSpec::Adaptive { size, max_neurons, min_neurons, grow_threshold, shrink_threshold } => {
+                    let mut layer = AdaptiveLayer::with_init(
+                        size,
+                        max_neurons,
+                        min_neurons,
+                        grow_threshold,
+                        shrink_threshold,
+                        self.adaptive_activation,
+                        self.init,
+                        self.history_length,
+                        &mut rng,
+                    );
+                    layer.set_use_bias(self.use_bias);
+                    layer.set_cooldown(self.adaptation_cooldown);
+                    layer.set_mutation(self.mutation);
+                    // Only the output layer may change width, as no layer rescales its inputs.
+                    layer.set_resizable(i == last);
+                    Layer::Adaptive(layer)
+                }
+                LayerSpec::Temporal(size) => {
+                    let in_size = layers.last().map_or(size, |previous| previous.output_size());
+                    let mut layer = TemporalLayer::with_delay_range(
+                        in_size,
+                        size,
+                        self.kernel,
+                        self.init,
+                        self.history_length,
+                        self.delay_range,
+                        &mut rng,
+                    );
+                    layer.set_use_bias(self.use_bias);
+                    if let Some(plasticity) = self.plasticity {
+                        layer.set_plasticity(plasticity);
+                    }
+                    layer.set_learn_plasticity(self.learn_plasticity);
+                    if let Some((refractory_period, fire_threshold)) = self.refractory {
+                        layer.set_refractory(refractory_period, fire_threshold);
+                    }
+                    Layer::Temporal(layer)
+                }
+            };
+            layers.push(layer);
+        }
+
+        NeuroForge {
+            layers,
+            emotional_memory: EmotionalMemory::with_policy(self.emotional_capacity, self.eviction_policy),
+            neuro_symbolic_layer: NeuroSymbolicLayer::new(),
+            emotional_state: 0.5,
+            emotional_config: self.emotional_config,
+            output_activation: self.output_activation,
+            frozen: false,
+            frozen_layers: Vec::new(),
+            skip_connections: Vec::new(),
+            heads: Vec::new(),
+            last_output: None,
+            rng,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_are_built_in_call_order() {
+        let network = NeuroForgeBuilder::new()
+            .temporal_layer(3)
+            .quantum_layer(3)
+            .adaptive_layer(3, 6, 1, 0.2)
+            .build()
+            .unwrap();
+        let kinds: Vec<&str> = network.layers.iter().map(|layer| layer.kind()).collect();
+        assert_eq!(kinds, vec!["temporal", "quantum", "adaptive"]);
+    }
+
+    #[test]
+    fn test_flags_match_seeded_legacy_constructor() {
+        let mut built = NeuroForgeBuilder::from_flags(&[2, 2], &[false, true], &[true, false]).seed(4).build().unwrap();
+        let mut legacy = NeuroForge::with_seed(&[2, 2], &[false, true], &[true, false], 4);
+        assert_eq!(built.forward(&[0.3, 0.9], 0.0), legacy.forward(&[0.3, 0.9], 0.0));
+    }
+
+    #[test]
+    fn test_quantum_layers_change_width() {
+        let mut network = NeuroForgeBuilder::new().quantum_layer(4).quantum_layer(2).quantum_layer(3).build().unwrap();
+        let shapes: Vec<(usize, usize)> = network.layers.iter().map(|layer| (layer.input_size(), layer.output_size())).collect();
+        assert_eq!(shapes, vec![(4, 4), (4, 2), (2, 3)]);
+        assert_eq!(network.forward(&[0.1, 0.2, 0.3, 0.4], 0.0).len(), 3);
+    }
+
+    #[test]
+    fn test_temporal_layers_change_width() {
+        let mut network = NeuroForgeBuilder::new().temporal_layer(4).temporal_layer(2).quantum_layer(3).build().unwrap();
+        let shapes: Vec<(usize, usize)> = network.layers.iter().map(|layer| (layer.input_size(), layer.output_size())).collect();
+        assert_eq!(shapes, vec![(4, 4), (4, 2), (2, 3)]);
+        assert_eq!(network.forward(&[0.1, 0.2, 0.3, 0.4], 0.0).len(), 3);
+
+        let inputs = vec![vec![0.1, 0.2, 0.3, 0.4]];
+        let stats = network.train(&inputs, &[vec![0.5, 0.5, 0.5]], 2, 0.1);
+        assert!(stats.iter().all(|epoch| epoch.mean_error.is_finite()));
+    }
+
+    #[test]
+    fn test_plasticity_applies_to_every_temporal_neuron() {
+        let network = NeuroForgeBuilder::new().temporal_layer(2).temporal_layer(3).plasticity(0.3).build().unwrap();
+        for layer in &network.layers {
+            let Layer::Temporal(layer) = layer else { panic!("expected a temporal layer") };
+            assert!(layer.neurons.iter().all(|neuron| neuron.plasticity() == 0.3));
+        }
+    }
+
+    #[test]
+    fn test_delay_range_applies_to_every_temporal_neuron() {
+        let mut network = NeuroForgeBuilder::new().temporal_layer(3).temporal_layer(2).delay_range(0.0, 10.0).seed(7).build().unwrap();
+        let delays = |network: &NeuroForge| -> Vec<f64> {
+            network.layers.iter()
+                .flat_map(|layer| match layer {
+                    Layer::Temporal(layer) => layer.neurons.iter().flat_map(|neuron| neuron.delays().to_vec()).collect(),
+                    _ => Vec::new(),
+                })
+                .collect()
+        };
+        assert!(delays(&network).iter().any(|&delay| delay > 1.0));
+        let inputs: Vec<Vec<f64>> = (0..4).map(|i| vec![0.2 * i as f64, 0.5, 0.1]).collect();
+        network.train(&inputs, &vec![vec![0.4, 0.6]; 4], 3, 0.5);
+        for layer in &network.layers {
+            let Layer::Temporal(layer) = layer else { panic!("expected a temporal layer") };
+            for neuron in &layer.neurons {
+                assert_eq!(neuron.delay_range(), (0.0, 10.0));
+                assert!(neuron.delays().iter().all(|delay| (0.0..=10.0).contains(delay)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_delay_range_is_reported() {
+        let build = |min, max| NeuroForgeBuilder::new().temporal_layer(2).delay_range(min, max).build().err();
+        assert_eq!(build(3.0, 1.0), Some(BuildError::InvalidDelayRange { min: 3.0, max: 1.0 }));
+        assert!(matches!(build(f64::NAN, 1.0), Some(BuildError::InvalidDelayRange { .. })));
+        assert_eq!(build(0.0, f64::INFINITY), Some(BuildError::InvalidDelayRange { min: 0.0, max: f64::INFINITY }));
+        assert_eq!(build(2.0, 2.0), None);
+        assert_eq!(build(3.0, 1.0).unwrap().to_string(), "delay range of 3 to 1 must be finite, with its minimum no greater than its maximum");
+    }
+
+    #[test]
+    fn test_invalid_emotional_clamp_is_reported() {
+        let build = |clamp| NeuroForgeBuilder::new()
+            .quantum_layer(2)
+            .emotional_config(EmotionalConfig { clamp, ..EmotionalConfig::default() })
+            .build()
+            .err();
+        assert_eq!(build((1.0, 0.0)), Some(BuildError::InvalidEmotionalClamp { min: 1.0, max: 0.0 }));
+        assert!(matches!(build((0.0, f64::NAN)), Some(BuildError::InvalidEmotionalClamp { .. })));
+        assert_eq!(build((0.0, f64::INFINITY)), None);
+        assert_eq!(
+            build((1.0, 0.0)).unwrap().to_string(),
+            "emotional state clamp of 1 to 0 must not be NaN, with its minimum no greater than its maximum"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_adjacent_layers_are_reported() {
+        let err = NeuroForgeBuilder::new().quantum_layer(2).quantum_layer(2).adaptive_layer(3, 6, 1, 0.1).build().err();
+        assert_eq!(err, Some(BuildError::IncompatibleLayers { layer: 2, expected: 3, got: 2 }));
+        assert_eq!(err.unwrap().to_string(), "layer 1 produces 2 outputs but layer 2 expects 3 inputs");
+    }
+
+    #[test]
+    fn test_adaptive_layer_outside_its_bounds_is_reported() {
+        let build = |size, max, min| NeuroForgeBuilder::new().quantum_layer(3).adaptive_layer(size, max, min, 0.1).build().err();
+        assert_eq!(build(3, 2, 4), Some(BuildError::InvalidNeuronBounds { layer: 1, initial: 3, min: 4, max: 2 }));
+        assert_eq!(build(3, 6, 4), Some(BuildError::InvalidNeuronBounds { layer: 1, initial: 3, min: 4, max: 6 }));
+        let err = build(3, 2, 1);
+        assert_eq!(err, Some(BuildError::InvalidNeuronBounds { layer: 1, initial: 3, min: 1, max: 2 }));
+        assert_eq!(err.unwrap().to_string(), "adaptive layer 1 starts at 3 neurons, outside its bounds of 1 to 2");
+        assert_eq!(build(3, 3, 3), None);
+    }
+
+    #[test]
+    fn test_inverted_adaptation_thresholds_are_reported() {
+        let build = |grow, shrink| NeuroForgeBuilder::new().quantum_layer(3).adaptive_layer_with_thresholds(3, 5, 1, grow, shrink).build().err();
+        let err = build(0.2, 0.8);
+        assert_eq!(err, Some(BuildError::InvalidAdaptationThresholds { layer: 1, grow: 0.2, shrink: 0.8 }));
+        assert_eq!(
+            err.unwrap().to_string(),
+            "adaptive layer 1 shrinks below 0.8 but grows above 0.2; the shrink threshold must not exceed the grow threshold"
+        );
+        assert!(matches!(build(f64::NAN, 0.2), Some(BuildError::InvalidAdaptationThresholds { .. })));
+        assert!(matches!(build(0.8, f64::NAN), Some(BuildError::InvalidAdaptationThresholds { .. })));
+        assert_eq!(build(0.8, 0.2), None);
+        assert_eq!(build(0.5, 0.5), None);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::error::BuildError;
+use crate::loss::Loss;
+use crate::schedule::LrSchedule;
+
+/// Number of past activations kept per neuron unless a layer is built with
+/// another history length.
+pub const DEFAULT_HISTORY_LENGTH: usize = 100;
+
+/// Options controlling a training run. See
+/// [`NeuroForge::train_with_config`](crate::NeuroForge::train_with_config).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainConfig {
+    pub epochs: usize,
+    /// Number of examples whose gradients are averaged into a single update.
+    pub batch_size: usize,
+    /// Weight decay strength; `l2_lambda * weight` is added to every weight gradient.
+    pub l2_lambda: f64,
+    /// Loss used for the reported error, the backward error signal and the
+    /// emotional state update.
+    pub loss: Loss,
+    /// Overrides the optimizer's learning rate at the start of every epoch.
+    /// `None` keeps the optimizer's own rate.
+    pub lr_schedule: Option<LrSchedule>,
+    /// Separate learning rates per layer family, replacing the single rate.
+    /// Combined with `lr_schedule`, every rate is scaled by the schedule's
+    /// rate relative to its first epoch; see [`LrSchedule::factor`].
+    pub learning_rates: Option<LearningRates>,
+    /// Maximum L2 norm of each layer's gradient; larger gradients are scaled down.
+    pub clip_grad_norm: Option<f64>,
+    /// Rejects the run before training starts if any input or target element
+    /// is NaN or infinite.
+    pub validate_inputs: bool,
+    /// Decays every emotional memory's intensity by this factor at the end of
+    /// each epoch; see [`EmotionalMemory::decay`](crate::emotional_memory::EmotionalMemory::decay).
+    pub memory_decay: Option<f64>,
+    /// Prints each epoch's learning rate and error to stdout.
+    pub verbose: bool,
+    /// Visits the examples in a new random order every epoch, drawn from the
+    /// network's seeded random number generator.
+    pub shuffle: bool,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        TrainConfig {
+            epochs: 1,
+            batch_size: 1,
+            l2_lambda: 0.0,
+            loss: Loss::Mse,
+            lr_schedule: None,
+            learning_rates: None,
+            clip_grad_norm: None,
+            validate_inputs: false,
+            memory_decay: None,
+            verbose: false,
+            shuffle: true,
+        }
+    }
+}
+
+/// Learning rate used by each layer family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LearningRates {
+    pub quantum: f64,
+    pub adaptive: f64,
+    pub temporal: f64,
+}
+
+impl LearningRates {
+    /// The same rate for every layer family.
+    pub fn uniform(learning_rate: f64) -> Self {
+        LearningRates { quantum: learning_rate, adaptive: learning_rate, temporal: learning_rate }
+    }
+
+    /// Every rate multiplied by `factor`.
+    pub fn scaled(&self, factor: f64) -> Self {
+        LearningRates {
+            quantum: self.quantum * factor,
+            adaptive: self.adaptive * factor,
+            temporal: self.temporal * factor,
+        }
+    }
+}
+
+/// How the emotional state follows the training error. After every example
+/// the state becomes `momentum * state + error_gain * error`, clamped into
+/// `clamp`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmotionalConfig {
+    pub momentum: f64,
+    pub error_gain: f64,
+    /// Inclusive `(min, max)` range of the emotional state.
+    pub clamp: (f64, f64),
+}
+
+impl Default for EmotionalConfig {
+    fn default() -> Self {
+        EmotionalConfig {
+            momentum: 0.9,
+            error_gain: 0.1,
+            clamp: (0.0, 1.0),
+        }
+    }
+}
+
+impl EmotionalConfig {
+    /// Checks that `clamp` is a range [`EmotionalConfig::update`] can clamp
+    /// into, which `f64::clamp` requires.
+    pub(crate) fn check(&self) -> Result<(), BuildError> {
+        let (min, max) = self.clamp;
+        if min <= max {
+            Ok(())
+        } else {
+            Err(BuildError::InvalidEmotionalClamp { min, max })
+        }
+    }
+
+    /// The emotional state after one example with loss `error`.
+    pub fn update(&self, emotional_state: f64, error: f64) -> f64 {
+        let (min, max) = self.clamp;
+        (self.momentum * emotional_state + self.error_gain * error).clamp(min, max)
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
+
+/// Inputs and targets as returned by [`load_csv`], one entry per row.
+pub type Dataset = (Vec<Vec<f64>>, Vec<Vec<f64>>);
+
+/// Reads a comma-separated file of numbers into `(inputs, targets)`, taking
+/// each row's `feature_cols` as an input and its `target_cols` as a target.
+///
+/// Blank lines are skipped, and a first line with no numeric cells is taken as
+/// a header. Any other cell in the selected columns that is not a number, or a
+/// row too short for them, is an `InvalidData` error naming the 1-based row.
+pub fn load_csv(path: &Path, feature_cols: Range<usize>, target_cols: Range<usize>) -> io::Result<Dataset> {
+    read_csv(BufReader::new(File::open(path)?), feature_cols, target_cols)
+}
+
+fn read_csv<R: BufRead>(reader: R, feature_cols: Range<usize>, target_cols: Range<usize>) -> io::Result<Dataset> {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = index + 1;
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        if row == 1 && cells.iter().all(|cell| cell.parse::<f64>().is_err()) {
+            continue;
+        }
+        inputs.push(parse_columns(&cells, feature_cols.clone(), row)?);
+        targets.push(parse_columns(&cells, target_cols.clone(), row)?);
+    }
+    Ok((inputs, targets))
+}
+
+fn parse_columns(cells: &[&str], columns: Range<usize>, row: usize) -> io::Result<Vec<f64>> {
+    if columns.end > cells.len() {
+        return Err(invalid_data(format!("row {} has {} columns, expected at least {}", row, cells.len(), columns.end)));
+    }
+    cells[columns.clone()]
+        .iter()
+        .zip(columns)
+        .map(|(cell, column)| {
+            cell.parse()
+                .map_err(|_| invalid_data(format!("row {} column {}: '{}' is not a number", row, column, cell)))
+        })
+        .collect()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_csv_splits_columns_and_skips_header() {
+        let data = "x1,x2,y\n0.0, 1.0, 1\n\n1.0,0.0,0\n";
+        let (inputs, targets) = read_csv(data.as_bytes(), 0..2, 2..3).unwrap();
+        assert_eq!(inputs, vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert_eq!(targets, vec![vec![1.0], vec![0.0]]);
+    }
+
+    #[test]
+    fn test_read_csv_reports_the_offending_row() {
+        let err = read_csv("1,2,3\n4,oops,6\n".as_bytes(), 0..2, 2..3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "row 2 column 1: 'oops' is not a number");
+
+        let err = read_csv("1,2,3\n4,5\n".as_bytes(), 0..2, 2..3).unwrap_err();
+        assert_eq!(err.to_string(), "row 2 has 2 columns, expected at least 3");
+    }
+}
@@ -1,35 +1,261 @@
 // emotional_memory.rs
 
-use std::collections::VecDeque;
+use std::cmp::Ordering;
 
+use crate::ring_buffer::RingBuffer;
+
+/// Which memory [`EmotionalMemory`] drops to make room once it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EvictionPolicy {
+    /// The oldest memory.
+    #[default]
+    Fifo,
+    /// The memory with the smallest emotional intensity, the oldest of them on a
+    /// tie. A NaN intensity counts as the smallest.
+    LowestIntensity,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EmotionalMemory {
-    memories: VecDeque<(Vec<f64>, f64)>, // (memory, emotional_intensity)
-    capacity: usize,
+    memories: RingBuffer<(Vec<f64>, f64, Option<String>)>, // (memory, emotional_intensity, tag)
+    forget_threshold: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    eviction_policy: EvictionPolicy,
 }
 
 impl EmotionalMemory {
     pub fn new(capacity: usize) -> Self {
+        Self::with_policy(capacity, EvictionPolicy::default())
+    }
+
+    pub fn with_policy(capacity: usize, eviction_policy: EvictionPolicy) -> Self {
         EmotionalMemory {
-            memories: VecDeque::new(),
-            capacity,
+            memories: RingBuffer::new(capacity),
+            forget_threshold: None,
+            eviction_policy,
         }
     }
 
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        self.eviction_policy
+    }
+
+    /// Makes [`EmotionalMemory::store`] first forget every memory whose intensity
+    /// has decayed below `threshold`. `None` keeps all memories until evicted.
+    pub fn set_forget_threshold(&mut self, threshold: Option<f64>) {
+        self.forget_threshold = threshold;
+    }
+
     pub fn store(&mut self, memory: Vec<f64>, emotional_intensity: f64) {
-        if self.memories.len() >= self.capacity {
-            self.memories.pop_front();
+        self.push(memory, emotional_intensity, None);
+    }
+
+    /// Stores a memory labelled with `tag`, for [`EmotionalMemory::recall_by_tag`].
+    /// Tagged memories share the capacity, and its eviction, with all others.
+    pub fn store_tagged(&mut self, memory: Vec<f64>, emotional_intensity: f64, tag: &str) {
+        self.push(memory, emotional_intensity, Some(tag.to_string()));
+    }
+
+    fn push(&mut self, memory: Vec<f64>, emotional_intensity: f64, tag: Option<String>) {
+        if let Some(threshold) = self.forget_threshold {
+            self.memories.retain(|(_, intensity, _)| *intensity >= threshold);
+        }
+        // A full buffer drops its oldest memory on push, which is the FIFO policy.
+        if self.eviction_policy == EvictionPolicy::LowestIntensity && self.memories.is_full() {
+            let weakest = self.memories
+                .iter()
+                .enumerate()
+                .min_by(|a, b| by_ascending_intensity(a.1.1, b.1.1))
+                .map(|(index, _)| index);
+            if let Some(index) = weakest {
+                self.memories.remove(index);
+            }
         }
-        self.memories.push_back((memory, emotional_intensity));
+        self.memories.push((memory, emotional_intensity, tag));
     }
 
+    /// Multiplies every stored intensity by `factor`, which must be in `[0, 1]`.
+    pub fn decay(&mut self, factor: f64) {
+        assert!((0.0..=1.0).contains(&factor), "decay factor must be in [0, 1]");
+        for (_, intensity, _) in self.memories.iter_mut() {
+            *intensity *= factor;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.memories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memories.is_empty()
+    }
+
+    /// Returns the memory whose intensity is closest to `current_emotion`.
+    /// Memories at a NaN distance, e.g. when either intensity is NaN, are
+    /// never recalled.
     pub fn recall(&self, current_emotion: f64) -> Option<Vec<f64>> {
         self.memories
             .iter()
-            .max_by(|a, b| {
-                let a_relevance = (current_emotion - a.1).abs();
-                let b_relevance = (current_emotion - b.1).abs();
-                a_relevance.partial_cmp(&b_relevance).unwrap()
-            })
+            .map(|(memory, intensity, _)| (memory, (current_emotion - intensity).abs()))
+            .filter(|(_, distance)| !distance.is_nan())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
             .map(|(memory, _)| memory.clone())
     }
+
+    /// Every memory stored with `tag`, newest first.
+    pub fn recall_by_tag(&self, tag: &str) -> Vec<Vec<f64>> {
+        self.memories
+            .iter()
+            .rev()
+            .filter(|(_, _, memory_tag)| memory_tag.as_deref() == Some(tag))
+            .map(|(memory, _, _)| memory.clone())
+            .collect()
+    }
+
+    /// Averages the `k` memories whose intensities are closest to
+    /// `current_emotion`, weighting each by the inverse of its distance. Memories
+    /// of differing lengths are averaged up to the shortest one. Memories at a
+    /// NaN distance are left out, as in [`EmotionalMemory::recall`]. Returns
+    /// `None` when no memory remains or `k` is zero.
+    pub fn recall_blend(&self, current_emotion: f64, k: usize) -> Option<Vec<f64>> {
+        let mut nearest: Vec<(&Vec<f64>, f64)> = self.memories
+            .iter()
+            .map(|(memory, intensity, _)| (memory, (current_emotion - intensity).abs()))
+            .filter(|(_, distance)| !distance.is_nan())
+            .collect();
+        nearest.sort_by(|a, b| a.1.total_cmp(&b.1));
+        nearest.truncate(k);
+
+        let length = nearest.iter().map(|(memory, _)| memory.len()).min()?;
+        let mut blend = vec![0.0; length];
+        let mut total_weight = 0.0;
+        for (memory, distance) in nearest {
+            // EPSILON keeps an exact match from dividing by zero while still dominating the blend.
+            let weight = 1.0 / (distance + f64::EPSILON);
+            for (value, &x) in blend.iter_mut().zip(memory.iter()) {
+                *value += weight * x;
+            }
+            total_weight += weight;
+        }
+
+        for value in &mut blend {
+            *value /= total_weight;
+        }
+        Some(blend)
+    }
+}
+
+/// Orders intensities from weakest to strongest, with NaN below every number
+/// so that a memory stored from a NaN emotional state is evicted first.
+fn by_ascending_intensity(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (a_nan, b_nan) => b_nan.cmp(&a_nan),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recall_returns_closest_memory() {
+        let mut memory = EmotionalMemory::new(10);
+        memory.store(vec![1.0], 0.1);
+        memory.store(vec![2.0], 0.5);
+        memory.store(vec![3.0], 0.9);
+        assert_eq!(memory.recall(0.45), Some(vec![2.0]));
+    }
+
+    #[test]
+    fn test_recall_by_tag_is_newest_first_with_shared_eviction() {
+        let mut memory = EmotionalMemory::new(3);
+        memory.store_tagged(vec![1.0], 0.5, "task_a");
+        memory.store_tagged(vec![2.0], 0.5, "task_b");
+        memory.store_tagged(vec![3.0], 0.5, "task_a");
+        memory.store(vec![4.0], 0.5);
+        assert_eq!(memory.recall_by_tag("task_a"), vec![vec![3.0]]);
+        assert_eq!(memory.recall_by_tag("task_b"), vec![vec![2.0]]);
+
+        memory.store_tagged(vec![5.0], 0.5, "task_a");
+        assert_eq!(memory.recall_by_tag("task_a"), vec![vec![5.0], vec![3.0]]);
+        assert!(memory.recall_by_tag("task_b").is_empty());
+    }
+
+    #[test]
+    fn test_lowest_intensity_policy_keeps_salient_memories() {
+        let mut memory = EmotionalMemory::with_policy(3, EvictionPolicy::LowestIntensity);
+        memory.store(vec![1.0], 0.9);
+        memory.store(vec![2.0], 0.1);
+        memory.store(vec![3.0], 0.1);
+        memory.store(vec![4.0], 0.5);
+        assert_eq!(memory.len(), 3);
+        assert_eq!(memory.recall(0.9), Some(vec![1.0]));
+        assert_eq!(memory.recall(0.0), Some(vec![3.0]));
+
+        let mut fifo = EmotionalMemory::new(3);
+        assert_eq!(fifo.eviction_policy(), EvictionPolicy::Fifo);
+        fifo.store(vec![1.0], 0.9);
+        fifo.store(vec![2.0], 0.1);
+        fifo.store(vec![3.0], 0.1);
+        fifo.store(vec![4.0], 0.5);
+        assert_eq!(fifo.recall(0.9), Some(vec![4.0]));
+    }
+
+    #[test]
+    fn test_lowest_intensity_policy_evicts_nan_intensities_first() {
+        let mut memory = EmotionalMemory::with_policy(2, EvictionPolicy::LowestIntensity);
+        memory.store(vec![1.0], 0.3);
+        memory.store(vec![2.0], f64::NAN);
+        memory.store(vec![3.0], 0.5);
+        assert_eq!(memory.len(), 2);
+        assert_eq!(memory.recall(0.3), Some(vec![1.0]));
+        assert_eq!(memory.recall(0.5), Some(vec![3.0]));
+    }
+
+    #[test]
+    fn test_decayed_memories_are_forgotten_on_store() {
+        let mut memory = EmotionalMemory::new(10);
+        memory.set_forget_threshold(Some(0.2));
+        memory.store(vec![1.0], 0.3);
+        memory.store(vec![2.0], 0.8);
+
+        memory.decay(0.5);
+        assert_eq!(memory.recall(0.15), Some(vec![1.0]));
+        memory.store(vec![3.0], 0.9);
+        assert_eq!(memory.len(), 2);
+        assert_eq!(memory.recall(0.15), Some(vec![2.0]));
+    }
+
+    #[test]
+    fn test_recall_blend_weights_by_inverse_distance() {
+        let mut memory = EmotionalMemory::new(10);
+        memory.store(vec![0.0, 4.0], 0.2);
+        memory.store(vec![3.0, 1.0, 7.0], 0.5);
+        memory.store(vec![100.0, 100.0], 0.9);
+
+        // Distances 0.1 and 0.2 give weights 10 and 5; the third memory is not among the 2 nearest.
+        let blend = memory.recall_blend(0.4, 2).unwrap();
+        assert_eq!(blend.len(), 2);
+        assert!((blend[0] - 2.0).abs() < 1e-9 && (blend[1] - 2.0).abs() < 1e-9);
+
+        assert_eq!(memory.recall_blend(0.4, 0), None);
+        assert_eq!(EmotionalMemory::new(10).recall_blend(0.4, 3), None);
+    }
+
+    #[test]
+    fn test_recall_skips_nan_distances() {
+        let mut memory = EmotionalMemory::new(10);
+        memory.store(vec![1.0], f64::NAN);
+        memory.store(vec![2.0], 0.9);
+        memory.store(vec![3.0], 0.4);
+
+        assert_eq!(memory.recall(0.0), Some(vec![3.0]));
+        let blend = memory.recall_blend(0.0, 3).unwrap();
+        assert!(blend[0].is_finite() && blend[0] > 2.0 && blend[0] < 3.0);
+        assert_eq!(memory.recall(f64::NAN), None);
+        assert_eq!(memory.recall_blend(f64::NAN, 3), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors returned by [`NeuroForge::try_forward`](crate::NeuroForge::try_forward).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForwardError {
+    /// The input length does not match the first layer's input size.
+    InputShape { expected: usize, got: usize },
+}
+
+impl fmt::Display for ForwardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForwardError::InputShape { expected, got } => {
+                write!(f, "input has {} elements but the first layer expects {}", got, expected)
+            }
+        }
+    }
+}
+
+impl Error for ForwardError {}
+
+/// Errors returned by [`NeuroForge::try_train_with_config`](crate::NeuroForge::try_train_with_config).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrainError {
+    /// Element `index` of input `sample` is NaN or infinite.
+    NonFiniteInput { sample: usize, index: usize },
+    /// Element `index` of target `sample` is NaN or infinite.
+    NonFiniteTarget { sample: usize, index: usize },
+}
+
+impl fmt::Display for TrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrainError::NonFiniteInput { sample, index } => {
+                write!(f, "input {} has a non-finite value at index {}", sample, index)
+            }
+            TrainError::NonFiniteTarget { sample, index } => {
+                write!(f, "target {} has a non-finite value at index {}", sample, index)
+            }
+        }
+    }
+}
+
+impl Error for TrainError {}
+
+/// Errors returned by [`NeuroForgeBuilder::build`](crate::NeuroForgeBuilder::build).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// Layer `layer` expects `expected` inputs but the layer before it
+    /// produces `got` outputs.
+    IncompatibleLayers { layer: usize, expected: usize, got: usize },
+    /// A skip connection must run forwards between two of the `layers` layers.
+    InvalidSkip { from: usize, to: usize, layers: usize },
+    /// A skip connection from layer `from` carries `got` values but layer `to`
+    /// expects `expected` inputs.
+    IncompatibleSkip { from: usize, to: usize, expected: usize, got: usize },
+    /// A head must branch from one of the `layers` layers.
+    InvalidBranch { branch: usize, layers: usize },
+    /// A head's first layer expects `expected` inputs but layer `branch`
+    /// produces `got` outputs.
+    IncompatibleHead { branch: usize, expected: usize, got: usize },
+    /// Adaptive layer `layer` starts at `initial` neurons, which must lie
+    /// between its `min` and `max`.
+    InvalidNeuronBounds { layer: usize, initial: usize, min: usize, max: usize },
+    /// Adaptive layer `layer` must not shrink above the activation it grows
+    /// at, and neither threshold may be NaN.
+    InvalidAdaptationThresholds { layer: usize, grow: f64, shrink: f64 },
+    /// The temporal delay range must be finite, with `min` no greater than `max`.
+    InvalidDelayRange { min: f64, max: f64 },
+    /// The emotional state's clamp must not contain NaN, and `min` must be no
+    /// greater than `max`.
+    InvalidEmotionalClamp { min: f64, max: f64 },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::IncompatibleLayers { layer, expected, got } => write!(
+                f,
+                "layer {} produces {} outputs but layer {} expects {} inputs",
+                layer - 1, got, layer, expected
+            ),
+            BuildError::InvalidSkip { from, to, layers } => write!(
+                f,
+                "cannot connect layer {} to layer {} in a network of {} layers",
+                from, to, layers
+            ),
+            BuildError::IncompatibleSkip { from, to, expected, got } => write!(
+                f,
+                "layer {} produces {} outputs but layer {} expects {} inputs from its skip connection",
+                from, got, to, expected
+            ),
+            BuildError::InvalidBranch { branch, layers } => write!(
+                f,
+                "cannot branch a head from layer {} in a network of {} layers",
+                branch, layers
+            ),
+            BuildError::IncompatibleHead { branch, expected, got } => write!(
+                f,
+                "layer {} produces {} outputs but the head's first layer expects {} inputs",
+                branch, got, expected
+            ),
+            BuildError::InvalidNeuronBounds { layer, initial, min, max } => write!(
+                f,
+                "adaptive layer {} starts at {} neurons, outside its bounds of {} to {}",
+                layer, initial, min, max
+            ),
+            BuildError::InvalidAdaptationThresholds { layer, grow, shrink } => write!(
+                f,
+                "adaptive layer {} shrinks below {} but grows above {}; the shrink threshold must not exceed the grow threshold",
+                layer, shrink, grow
+            ),
+            BuildError::InvalidDelayRange { min, max } => write!(
+                f,
+                "delay range of {} to {} must be finite, with its minimum no greater than its maximum",
+                min, max
+            ),
+            BuildError::InvalidEmotionalClamp { min, max } => write!(
+                f,
+                "emotional state clamp of {} to {} must not be NaN, with its minimum no greater than its maximum",
+                min, max
+            ),
+        }
+    }
+}
+
+impl Error for BuildError {}
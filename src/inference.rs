@@ -0,0 +1,210 @@
+use ndarray::{Array1, Array2};
+
+use crate::activation::{Activation, OutputActivation};
+use crate::quantum_neuron::QuantumNeuron;
+use crate::temporal_plasticity::TemporalNeuron;
+
+/// Read-only copy of a trained network's parameters, created by
+/// [`NeuroForge::into_inference`](crate::NeuroForge::into_inference).
+///
+/// [`InferenceModel::predict`] takes `&self`: quantum neurons are evaluated
+/// from their stored phase without measuring or rotating, and nothing is
+/// recorded, so the model is `Send + Sync` and can be shared behind an `Arc`.
+/// Symbolic rules are not carried over; predictions are the activated neural
+/// outputs only.
+#[derive(Clone)]
+pub struct InferenceModel {
+    pub(crate) layers: Vec<InferenceLayer>,
+    pub(crate) skip_connections: Vec<(usize, usize)>,
+    pub(crate) input_size: Option<usize>,
+    pub(crate) output_activation: OutputActivation,
+}
+
+#[derive(Clone)]
+pub(crate) enum InferenceLayer {
+    Quantum { weights: Array2<f64>, bias: Array1<f64>, neurons: Vec<QuantumNeuron> },
+    Adaptive { weights: Array2<f64>, bias: Array1<f64>, activation: Activation },
+    Temporal { neurons: Vec<TemporalNeuron> },
+}
+
+impl InferenceLayer {
+    fn predict(&self, input: &[f64]) -> Vec<f64> {
+        match self {
+            InferenceLayer::Quantum { weights, bias, neurons } => {
+                let weighted_inputs = weights.dot(&ndarray::aview1(input)) + bias;
+                neurons.iter().zip(weighted_inputs.iter()).map(|(neuron, &x)| neuron.peek(x)).collect()
+            }
+            InferenceLayer::Adaptive { weights, bias, activation } => {
+                let weighted_sums = weights.dot(&ndarray::aview1(input)) + bias;
+                weighted_sums.iter().map(|&x| activation.apply(x)).collect()
+            }
+            InferenceLayer::Temporal { neurons } => neurons.iter().map(|neuron| neuron.output(input, 0.0)).collect(),
+        }
+    }
+}
+
+impl InferenceModel {
+    /// Number of inputs the first layer expects, or `None` for a network without layers.
+    pub fn input_size(&self) -> Option<usize> {
+        self.input_size
+    }
+
+    /// Outputs of the network for `input`, presented at time 0 as during
+    /// training. Panics if `input` does not fit the first layer.
+    pub fn predict(&self, input: &[f64]) -> Vec<f64> {
+        if let Some(expected) = self.input_size {
+            assert_eq!(input.len(), expected, "expected {} inputs, got {}", expected, input.len());
+        }
+        let output = crate::run_with_skips(&self.skip_connections, self.layers.len(), input.to_vec(), |l, x| {
+            self.layers[l].predict(x)
+        });
+        self.output_activation.apply(&output)
+    }
+}
+
+/// Int8 copy of a network's parameters, created by
+/// [`NeuroForge::quantize`](crate::NeuroForge::quantize).
+///
+/// Each quantum and adaptive layer stores its weights as `i8` with one scale
+/// per layer, `max |w| / 127`, so a weight `w` becomes `round(w / scale)`.
+/// [`QuantizedModel::predict`] multiplies the integer weights with the input
+/// and rescales the sums, which is the dequantized product. Biases, quantum
+/// phases and temporal layers stay in floating point. As with
+/// [`InferenceModel`], symbolic rules are not carried over.
+#[derive(Clone)]
+pub struct QuantizedModel {
+    pub(crate) layers: Vec<QuantizedLayer>,
+    pub(crate) skip_connections: Vec<(usize, usize)>,
+    pub(crate) input_size: Option<usize>,
+    pub(crate) output_activation: OutputActivation,
+}
+
+#[derive(Clone)]
+pub(crate) enum QuantizedLayer {
+    Quantum { weights: QuantizedWeights, bias: Array1<f64>, neurons: Vec<QuantumNeuron> },
+    Adaptive { weights: QuantizedWeights, bias: Array1<f64>, activation: Activation },
+    Temporal { neurons: Vec<TemporalNeuron> },
+}
+
+#[derive(Clone)]
+pub(crate) struct QuantizedWeights {
+    values: Array2<i8>,
+    scale: f64,
+}
+
+impl QuantizedWeights {
+    fn new(weights: &Array2<f64>) -> Self {
+        let largest = weights.iter().fold(0.0_f64, |largest, w| largest.max(w.abs()));
+        // An all-zero layer quantizes to zeros at any scale.
+        let scale = if largest > 0.0 { largest / i8::MAX as f64 } else { 1.0 };
+        QuantizedWeights { values: weights.mapv(|w| (w / scale).round() as i8), scale }
+    }
+
+    /// `weights · input + bias`, accumulating the integer weights before scaling.
+    fn weighted_sums(&self, input: &[f64], bias: &Array1<f64>) -> Vec<f64> {
+        self.values.outer_iter()
+            .zip(bias)
+            .map(|(row, &b)| self.scale * row.iter().zip(input).map(|(&q, &x)| q as f64 * x).sum::<f64>() + b)
+            .collect()
+    }
+}
+
+impl From<InferenceLayer> for QuantizedLayer {
+    fn from(layer: InferenceLayer) -> Self {
+        match layer {
+            InferenceLayer::Quantum { weights, bias, neurons } => {
+                QuantizedLayer::Quantum { weights: QuantizedWeights::new(&weights), bias, neurons }
+            }
+            InferenceLayer::Adaptive { weights, bias, activation } => {
+                QuantizedLayer::Adaptive { weights: QuantizedWeights::new(&weights), bias, activation }
+            }
+            InferenceLayer::Temporal { neurons } => QuantizedLayer::Temporal { neurons },
+        }
+    }
+}
+
+impl QuantizedLayer {
+    fn predict(&self, input: &[f64]) -> Vec<f64> {
+        match self {
+            QuantizedLayer::Quantum { weights, bias, neurons } => {
+                neurons.iter().zip(weights.weighted_sums(input, bias)).map(|(neuron, x)| neuron.peek(x)).collect()
+            }
+            QuantizedLayer::Adaptive { weights, bias, activation } => {
+                weights.weighted_sums(input, bias).into_iter().map(|x| activation.apply(x)).collect()
+            }
+            QuantizedLayer::Temporal { neurons } => neurons.iter().map(|neuron| neuron.output(input, 0.0)).collect(),
+        }
+    }
+}
+
+impl QuantizedModel {
+    /// Number of inputs the first layer expects, or `None` for a network without layers.
+    pub fn input_size(&self) -> Option<usize> {
+        self.input_size
+    }
+
+    /// Outputs of the quantized network for `input`, presented at time 0.
+    /// Panics if `input` does not fit the first layer.
+    pub fn predict(&self, input: &[f64]) -> Vec<f64> {
+        if let Some(expected) = self.input_size {
+            assert_eq!(input.len(), expected, "expected {} inputs, got {}", expected, input.len());
+        }
+        let output = crate::run_with_skips(&self.skip_connections, self.layers.len(), input.to_vec(), |l, x| {
+            self.layers[l].predict(x)
+        });
+        self.output_activation.apply(&output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NeuroForge;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_shared_model_predicts_from_many_threads() {
+        let mut network = NeuroForge::with_seed(&[3, 3, 3], &[false, true, false], &[false, false, true], 46);
+        network.freeze();
+        let expected = network.forward(&[0.1, 0.5, 0.9], 0.0);
+
+        let model: Arc<InferenceModel> = Arc::new(network.into_inference());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let model = Arc::clone(&model);
+                std::thread::spawn(move || model.predict(&[0.1, 0.5, 0.9]))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_quantized_weights_round_trip_within_half_a_step() {
+        let weights = ndarray::array![[0.5, -1.27], [0.013, 0.0]];
+        let quantized = QuantizedWeights::new(&weights);
+        assert_eq!(quantized.scale, 0.01);
+        assert_eq!(quantized.values, ndarray::array![[50, -127], [1, 0]]);
+        let sums = quantized.weighted_sums(&[1.0, 2.0], &ndarray::array![0.1, -0.1]);
+        assert!((sums[0] - (0.5 - 2.54 + 0.1)).abs() < 1e-12);
+        assert!((sums[1] - (0.01 - 0.1)).abs() < 1e-12);
+        assert!(QuantizedWeights::new(&Array2::zeros((2, 2))).values.iter().all(|&q| q == 0));
+    }
+
+    #[test]
+    fn test_quantized_xor_model_tracks_the_float_model() {
+        let mut network = NeuroForge::with_seed(&[2, 2, 1], &[true, true, false], &[false, false, false], 57);
+        let inputs = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+        network.train(&inputs, &targets, 200, 0.1);
+        network.freeze();
+
+        let quantized = network.quantize();
+        for input in &inputs {
+            let expected = network.forward(input, 0.0);
+            let output = quantized.predict(input);
+            assert!((output[0] - expected[0]).abs() < 0.05, "{:?} vs {:?}", output, expected);
+        }
+    }
+}
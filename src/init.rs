@@ -0,0 +1,60 @@
+use rand::Rng;
+
+/// Scheme for drawing a layer's initial weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightInit {
+    /// Uniform in `-limit..limit`, regardless of layer size.
+    Uniform(f64),
+    /// Uniform in `±sqrt(6 / (fan_in + fan_out))` (Glorot & Bengio), suited to
+    /// sigmoid and tanh layers.
+    XavierUniform,
+    /// Normal with mean 0 and standard deviation `sqrt(2 / fan_in)` (He et al.),
+    /// suited to ReLU layers.
+    HeNormal,
+}
+
+impl Default for WeightInit {
+    fn default() -> Self {
+        WeightInit::Uniform(1.0)
+    }
+}
+
+impl WeightInit {
+    /// Draws one weight for a layer with `fan_in` inputs and `fan_out` outputs.
+    pub fn sample<R: Rng + ?Sized>(&self, fan_in: usize, fan_out: usize, rng: &mut R) -> f64 {
+        match *self {
+            WeightInit::Uniform(limit) => rng.gen_range(-limit..limit),
+            WeightInit::XavierUniform => {
+                let limit = (6.0 / (fan_in + fan_out).max(1) as f64).sqrt();
+                rng.gen_range(-limit..limit)
+            }
+            WeightInit::HeNormal => {
+                // Box-Muller transform; `1 - u` keeps the logarithm's argument in (0, 1].
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen();
+                let standard_normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                standard_normal * (2.0 / fan_in.max(1) as f64).sqrt()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_schemes_scale_with_fan_in() {
+        let mut rng = ChaCha12Rng::seed_from_u64(3);
+        let limit = (6.0f64 / 200.0).sqrt();
+        assert!((0..1000).all(|_| WeightInit::XavierUniform.sample(100, 100, &mut rng).abs() < limit));
+
+        let samples: Vec<f64> = (0..20000).map(|_| WeightInit::HeNormal.sample(50, 10, &mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.01);
+        assert!((variance - 2.0 / 50.0).abs() < 0.004);
+    }
+}
@@ -1,233 +1,3220 @@
+use rand::seq::SliceRandom;
 use rand::Rng;
-use ndarray::{Array, Array1, Array2};
+#[cfg(feature = "rayon")]
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use ndarray::{Array, Array1, Array2, Axis};
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{self, BufReader, BufWriter};
+#[cfg(feature = "serde")]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use serde_json::{json, Value};
+use std::ops::ControlFlow;
 
 pub mod adaptive_architecture;
 pub mod quantum_neuron;
 pub mod emotional_memory;
 pub mod temporal_plasticity;
 pub mod neuro_symbolic;
+pub mod error;
+pub mod activation;
+pub mod optimizer;
+pub mod config;
+pub mod loss;
+pub mod schedule;
+pub mod init;
+pub mod builder;
+pub mod metrics;
+pub mod training;
+pub mod preprocessing;
+pub mod inference;
+pub mod ring_buffer;
+#[cfg(feature = "csv")]
+pub mod dataset;
 
-use crate::quantum_neuron::QuantumNeuron;
+use crate::quantum_neuron::{QuantumMode, QuantumNeuron};
 use crate::adaptive_architecture::AdaptiveLayer;
-use crate::temporal_plasticity::TemporalNeuron;
+pub use crate::activation::OutputActivation;
+use crate::optimizer::{clip_grad_norm, default_optimizer, matrix_state_sources, Optimizer, Sgd};
+use crate::temporal_plasticity::{TemporalKernel, TemporalLayer};
 use crate::emotional_memory::EmotionalMemory;
 use crate::neuro_symbolic::NeuroSymbolicLayer;
+pub use crate::error::{BuildError, ForwardError, TrainError};
+pub use crate::config::{EmotionalConfig, LearningRates, TrainConfig};
+pub use crate::loss::Loss;
+pub use crate::schedule::LrSchedule;
+pub use crate::init::WeightInit;
+pub use crate::builder::NeuroForgeBuilder;
+pub use crate::inference::{InferenceModel, QuantizedModel};
+use crate::inference::InferenceLayer;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NeuroForge {
-    quantum_layers: Vec<QuantumLayer>,
-    adaptive_layers: Vec<AdaptiveLayer>,
-    temporal_layers: Vec<TemporalLayer>,
+    layers: Vec<Layer>,
     emotional_memory: EmotionalMemory,
     neuro_symbolic_layer: NeuroSymbolicLayer,
     emotional_state: f64,
+    emotional_config: EmotionalConfig,
+    output_activation: OutputActivation,
+    frozen: bool,
+    // Indices of the layers held fixed by `freeze_layer`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    frozen_layers: Vec<usize>,
+    // `(from, to)` pairs: layer `from`'s output is added to layer `to`'s input.
+    #[cfg_attr(feature = "serde", serde(default))]
+    skip_connections: Vec<(usize, usize)>,
+    // Extra output tails added with `add_head`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    heads: Vec<Head>,
+    // Output of the last `forward`, which `train_step` learns from.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_output: Option<Vec<f64>>,
+    // ChaCha12 is the generator behind `StdRng`; it is named directly so its
+    // state can be saved alongside the weights.
+    rng: ChaCha12Rng,
 }
 
+/// A single layer of the network. Layers run in declaration order.
+// A network holds only a handful of layers, so the size gap between variants
+// is not worth an extra indirection on every call.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Layer {
+    Quantum(QuantumLayer),
+    Adaptive(AdaptiveLayer),
+    Temporal(TemporalLayer),
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct QuantumLayer {
     neurons: Vec<QuantumNeuron>,
     weights: Array2<f64>,
+    // Added to the weighted inputs before activation; stays zero when `use_bias` is off.
+    bias: Array1<f64>,
+    use_bias: bool,
+    // Index pairs of entangled neurons; see `QuantumLayer::entangle_pairs`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    entangled_pairs: Vec<(usize, usize)>,
+    // Input of the last forward pass, which the weight gradients are taken against.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_input: Array1<f64>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_optimizer"))]
+    optimizer: Box<dyn Optimizer>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gradient_sum: Array2<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bias_gradient_sum: Array1<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mix_gradient_sum: Array1<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    accumulated_steps: usize,
+}
+
+/// Summary of one training epoch, as returned by [`NeuroForge::train`] and
+/// the other training methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochStats {
+    pub epoch: usize,
+    /// Loss averaged over every example in the epoch; 0 for an epoch without examples.
+    pub mean_error: f64,
+    /// Emotional state at the end of the epoch.
+    pub emotional_state: f64,
+    /// Total number of neurons across all layers at the end of the epoch.
+    pub neuron_count: usize,
+    /// Mean error over the validation set after the epoch, when training with
+    /// [`NeuroForge::train_with_validation`].
+    pub validation_error: Option<f64>,
+}
+
+/// An extra output tail of a multi-head network, fed from the output of the
+/// main layer at index `branch`; see [`NeuroForge::add_head`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Head {
+    branch: usize,
+    layers: Vec<Layer>,
+    loss: Loss,
+}
+
+/// One layer's entry in [`NeuroForge::architecture_summary`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerInfo {
+    /// `"quantum"`, `"adaptive"` or `"temporal"`.
+    pub kind: &'static str,
+    pub input_size: usize,
+    /// Current number of neurons, which is also the layer's output size.
+    pub neuron_count: usize,
+}
+
+/// Summary of one layer's weights, from [`NeuroForge::weight_stats`]. Biases
+/// are not included. A layer without weights reports zeros throughout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightStats {
+    pub layer_index: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub std: f64,
+    /// Fraction of weights whose magnitude is below [`NEAR_ZERO_WEIGHT`],
+    /// which points at dead units.
+    pub frac_near_zero: f64,
+}
+
+/// Magnitude below which [`WeightStats::frac_near_zero`] counts a weight.
+pub const NEAR_ZERO_WEIGHT: f64 = 1e-4;
+
+/// Copy of a network's numeric state taken by [`NeuroForge::snapshot`]: the
+/// layers' weights, biases, delays and quantum phases, the symbolic rule
+/// weights, the emotional memory and state, and the random generator. The
+/// symbolic rules themselves are not included.
+#[derive(Clone)]
+pub struct NeuroForgeSnapshot {
+    layers: Vec<Layer>,
+    heads: Vec<Head>,
+    rule_weights: Vec<(String, f64)>,
+    emotional_memory: EmotionalMemory,
+    emotional_state: f64,
+    rng: ChaCha12Rng,
+}
+
+/// Outcome of [`NeuroForge::train_with_early_stopping`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarlyStoppingReport {
+    /// Index of the last epoch that ran.
+    pub stopped_epoch: usize,
+    /// Index of the epoch with the lowest mean error; its weights are restored.
+    pub best_epoch: usize,
+    pub best_error: f64,
+    /// Whether training stopped before `max_epochs` because of the patience limit.
+    pub triggered: bool,
 }
 
-struct TemporalLayer {
-    neurons: Vec<TemporalNeuron>,
-}
+/// Minimum decrease in mean epoch error that counts as an improvement.
+const EARLY_STOPPING_TOLERANCE: f64 = 1e-6;
+
+impl NeuroForge {
+    /// Builds a network from parallel per-layer arrays: layer `i` has
+    /// `layer_sizes[i]` neurons and is adaptive if `adaptive_layers[i]`,
+    /// otherwise temporal if `temporal_layers[i]`, otherwise quantum. Layers
+    /// beyond the shortest slice are dropped; [`NeuroForgeBuilder`] avoids
+    /// keeping the arrays aligned by hand. Quantum layers take their inputs
+    /// from the layer before them, as described on
+    /// [`NeuroForgeBuilder::quantum_layer`].
+    ///
+    /// This and the other array-based constructors do not check that adjacent
+    /// layers fit together, so a mismatch panics at the first forward pass.
+    /// [`NeuroForge::try_new`] and [`NeuroForgeBuilder::build`] check up front.
+    pub fn new(layer_sizes: &[usize], adaptive_layers: &[bool], temporal_layers: &[bool]) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).build_unchecked()
+    }
+
+    /// Like [`NeuroForge::new`], returning an error if a layer does not accept
+    /// as many inputs as the layer before it produces.
+    pub fn try_new(layer_sizes: &[usize], adaptive_layers: &[bool], temporal_layers: &[bool]) -> Result<Self, BuildError> {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).build()
+    }
+
+    /// Builds a network whose initialization and stochastic behaviour are fully
+    /// determined by `seed`, so two networks built with the same seed are identical.
+    pub fn with_seed(layer_sizes: &[usize], adaptive_layers: &[bool], temporal_layers: &[bool], seed: u64) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).seed(seed).build_unchecked()
+    }
+
+    /// Builds a network whose temporal layers all use `kernel`.
+    pub fn with_temporal_kernel(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        kernel: TemporalKernel,
+    ) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).temporal_kernel(kernel).build_unchecked()
+    }
+
+    /// Builds a network whose quantum neurons all use `mode`. With
+    /// [`QuantumMode::Deterministic`] the quantum layers' forward pass no longer
+    /// depends on the random number generator.
+    pub fn with_quantum_mode(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        mode: QuantumMode,
+    ) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).quantum_mode(mode).build_unchecked()
+    }
+
+    /// Builds a network whose quantum neurons output values in `[0, 1]` when
+    /// `unit_output` is true, rather than superposed amplitudes in `[-1, 1]`.
+    pub fn with_unit_quantum_output(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        unit_output: bool,
+    ) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).unit_quantum_output(unit_output).build_unchecked()
+    }
+
+    /// Builds a network whose neurons have a learnable bias when `use_bias` is
+    /// true, as with every other constructor, or pass through the origin when false.
+    pub fn with_bias(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        use_bias: bool,
+    ) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).use_bias(use_bias).build_unchecked()
+    }
+
+    /// Builds a network whose initial weights are drawn from `init` instead of
+    /// the default `Uniform(1.0)`.
+    pub fn with_init(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        init: WeightInit,
+    ) -> Self {
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).weight_init(init).build_unchecked()
+    }
+
+    /// Builds a network whose emotional state follows the training error as
+    /// described by `emotional_config`. Panics if its clamp has a NaN bound
+    /// or a minimum above its maximum.
+    pub fn with_emotional_config(
+        layer_sizes: &[usize],
+        adaptive_layers: &[bool],
+        temporal_layers: &[bool],
+        emotional_config: EmotionalConfig,
+    ) -> Self {
+        if let Err(err) = emotional_config.check() {
+            panic!("{}", err);
+        }
+        NeuroForgeBuilder::from_flags(layer_sizes, adaptive_layers, temporal_layers).emotional_config(emotional_config).build_unchecked()
+    }
+
+    /// Runs a forward pass, panicking if `input` does not fit the first layer.
+    /// Use [`NeuroForge::try_forward`] to handle bad input gracefully.
+    pub fn forward(&mut self, input: &[f64], time: f64) -> Vec<f64> {
+        self.try_forward(input, time).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs a forward pass after checking `input` against the first layer's
+    /// expected size.
+    pub fn try_forward(&mut self, input: &[f64], time: f64) -> Result<Vec<f64>, ForwardError> {
+        if let Some(expected) = self.input_size() {
+            if input.len() != expected {
+                return Err(ForwardError::InputShape { expected, got: input.len() });
+            }
+        }
+
+        let neural_output = self.forward_layers(input, time, |_, _| {});
+        Ok(self.finish_forward(neural_output))
+    }
+
+    /// Runs [`NeuroForge::forward`] and also returns the output of every layer
+    /// in order, before the output activation and symbolic rules, e.g. to
+    /// extract features from an intermediate layer. Panics if `input` does not
+    /// fit the first layer.
+    pub fn forward_with_activations(&mut self, input: &[f64], time: f64) -> (Vec<f64>, Vec<Vec<f64>>) {
+        if let Some(expected) = self.input_size() {
+            if input.len() != expected {
+                panic!("{}", ForwardError::InputShape { expected, got: input.len() });
+            }
+        }
+
+        let mut activations = Vec::with_capacity(self.layers.len());
+        let neural_output = self.forward_layers(input, time, |_, output| activations.push(output.to_vec()));
+        (self.finish_forward(neural_output), activations)
+    }
+
+    /// Runs the main layers on `input`, calling `tap` with each layer's index
+    /// and output, and returns the last layer's raw output.
+    fn forward_layers(&mut self, input: &[f64], time: f64, mut tap: impl FnMut(usize, &[f64])) -> Vec<f64> {
+        let layer_count = self.layers.len();
+        if self.frozen {
+            let layers = &self.layers;
+            run_with_skips(&self.skip_connections, layer_count, input.to_vec(), |l, x| {
+                let output = layers[l].infer(x, time);
+                tap(l, &output);
+                output
+            })
+        } else {
+            let (layers, rng, emotional_state) = (&mut self.layers, &mut self.rng, self.emotional_state);
+            run_with_skips(&self.skip_connections, layer_count, input.to_vec(), |l, x| {
+                let output = layers[l].forward(x, emotional_state, time, rng);
+                tap(l, &output);
+                output
+            })
+        }
+    }
+
+    /// Applies the output activation and symbolic rules to the last layer's
+    /// output and records the result in emotional memory and, unless the
+    /// network is frozen, for `train_step`. Frozen layers cache nothing to
+    /// backpropagate through, so a frozen output cannot be learned from.
+    fn finish_forward(&mut self, neural_output: Vec<f64>) -> Vec<f64> {
+        let output = self.neuro_symbolic_layer.process(self.output_activation.apply(&neural_output));
+        self.emotional_memory.store(output.clone(), self.emotional_state);
+        if !self.frozen {
+            self.last_output = Some(output.clone());
+        }
+        output
+    }
+
+    /// Adds an output head: the layers of `tail`, fed from the output of main
+    /// layer `branch`, and trained with `loss` by [`NeuroForge::train_multihead`].
+    /// Returns the head's index in the outputs of
+    /// [`NeuroForge::forward_multihead`], where index 0 is the main output.
+    ///
+    /// `tail` is built as a network of its own, so its first layer must have
+    /// the width of layer `branch`'s output. A tail without layers passes the
+    /// branch output through unchanged. Heads output their last layer's values
+    /// without an output activation or symbolic rules, and their layers are
+    /// trained but never resized, and layer `branch` should not resize either.
+    /// Plain [`NeuroForge::forward`] ignores heads. Panics if `loss` is a
+    /// Huber loss whose `delta` is not positive.
+    pub fn add_head(&mut self, branch: usize, tail: NeuroForgeBuilder, loss: Loss) -> Result<usize, BuildError> {
+        loss.assert_valid();
+        if branch >= self.layers.len() {
+            return Err(BuildError::InvalidBranch { branch, layers: self.layers.len() });
+        }
+        let layers = tail.build()?.layers;
+        let got = self.layers[branch].output_size();
+        if let Some(first) = layers.first() {
+            if first.input_size() != got {
+                return Err(BuildError::IncompatibleHead { branch, expected: first.input_size(), got });
+            }
+        }
+        self.heads.push(Head { branch, layers, loss });
+        Ok(self.heads.len())
+    }
+
+    /// Number of heads added with [`NeuroForge::add_head`], not counting the main output.
+    pub fn head_count(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Runs the main layers once and every head from its branch point. The
+    /// first output is exactly what [`NeuroForge::forward`] returns, and the
+    /// rest are the heads' outputs in the order they were added. Panics if
+    /// `input` does not fit the first layer.
+    pub fn forward_multihead(&mut self, input: &[f64], time: f64) -> Vec<Vec<f64>> {
+        if let Some(expected) = self.input_size() {
+            if input.len() != expected {
+                panic!("{}", ForwardError::InputShape { expected, got: input.len() });
+            }
+        }
+
+        let mut branch_outputs: Vec<Option<Vec<f64>>> = vec![None; self.layers.len()];
+        let branches: Vec<usize> = self.heads.iter().map(|head| head.branch).collect();
+        let neural_output = self.forward_layers(input, time, |l, output| {
+            if branches.contains(&l) {
+                branch_outputs[l] = Some(output.to_vec());
+            }
+        });
+        let mut outputs = vec![self.finish_forward(neural_output)];
+
+        for head in &mut self.heads {
+            let mut current = branch_outputs[head.branch].clone().expect("every branch layer ran");
+            for layer in &mut head.layers {
+                current = if self.frozen {
+                    layer.infer(&current, time)
+                } else {
+                    layer.forward(&current, self.emotional_state, time, &mut self.rng)
+                };
+            }
+            outputs.push(current);
+        }
+        outputs
+    }
+
+    /// Trains the main output and every head together with plain SGD at
+    /// `learning_rate`. `targets[i]` holds one target per output of
+    /// [`NeuroForge::forward_multihead`]: the main output's first, learned with
+    /// [`Loss::default`], then each head's, learned with the head's own loss.
+    /// Head errors are summed into the main layers at their branch points.
+    /// An epoch's `mean_error` is the mean over examples of the summed losses.
+    ///
+    /// Panics if the network is frozen or a sample has the wrong number of targets.
+    pub fn train_multihead(&mut self, inputs: &[Vec<f64>], targets: &[Vec<Vec<f64>>], epochs: usize, learning_rate: f64) -> Vec<EpochStats> {
+        assert!(!self.frozen, "cannot train a frozen network; call unfreeze first");
+        let loss = Loss::default();
+        let optimizer = Sgd::new(learning_rate);
+        self.set_optimizer(&optimizer);
+        for layer in self.heads.iter_mut().flat_map(|head| head.layers.iter_mut()) {
+            layer.set_optimizer(optimizer.fresh());
+        }
+
+        (0..epochs)
+            .map(|epoch| {
+                let mut total_error = 0.0;
+                for (input, sample_targets) in inputs.iter().zip(targets) {
+                    assert_eq!(sample_targets.len(), self.heads.len() + 1, "expected one target per output");
+                    let outputs = self.forward_multihead(input, 0.0);
+
+                    let mut branch_errors: Vec<Option<Vec<f64>>> = vec![None; self.layers.len()];
+                    for ((head, output), target) in self.heads.iter_mut().zip(&outputs[1..]).zip(&sample_targets[1..]) {
+                        total_error += head.loss.value(output, target);
+                        let mut error = head.loss.gradient(output, target);
+                        for layer in head.layers.iter_mut().rev() {
+                            error = layer.accumulate_gradients(&error);
+                        }
+                        match &mut branch_errors[head.branch] {
+                            Some(pending) => add_into(pending, &error),
+                            pending => *pending = Some(error),
+                        }
+                    }
+                    total_error += self.accumulate_gradients_with(&outputs[0], &sample_targets[0], loss, 1.0, branch_errors);
+                    self.update_emotional_state(&outputs[0], &sample_targets[0], loss);
+
+                    for layer in self.heads.iter_mut().flat_map(|head| head.layers.iter_mut()) {
+                        layer.apply_gradients(0.0, None);
+                    }
+                    self.apply_gradients(learning_rate, 0.0, None);
+                    self.adapt_architecture();
+                }
+
+                EpochStats {
+                    epoch,
+                    mean_error: total_error / inputs.len() as f64,
+                    emotional_state: self.emotional_state,
+                    neuron_count: self.layers.iter().map(|layer| layer.output_size()).sum(),
+                    validation_error: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Runs [`NeuroForge::forward`] on each `(input, timestamp)` pair in
+    /// order, so temporal layers see every sample at its own time and build
+    /// their activation history along the real timeline. Panics if a
+    /// timestamp is earlier than the one before it, or if an input does not fit
+    /// the first layer.
+    pub fn forward_sequence(&mut self, samples: &[(Vec<f64>, f64)]) -> Vec<Vec<f64>> {
+        if let Some(pair) = samples.windows(2).find(|pair| pair[1].1 < pair[0].1) {
+            panic!("sample timestamps must not decrease, got {} after {}", pair[1].1, pair[0].1);
+        }
+        samples.iter().map(|(input, time)| self.forward(input, *time)).collect()
+    }
+
+    /// Learns from the most recent [`NeuroForge::forward`] against `target`:
+    /// one backward pass and plain SGD update at `learning_rate`, the emotional
+    /// state update and an architecture adaptation step, exactly as `train`
+    /// does for each example. Returns the MSE loss of that forward's output.
+    ///
+    /// Panics if no forward pass has run since the last step, or if the
+    /// network is frozen. Forward passes made while frozen do not count.
+    pub fn train_step(&mut self, target: &[f64], learning_rate: f64) -> f64 {
+        assert!(!self.frozen, "cannot train a frozen network; call unfreeze first");
+        let output = self.last_output.take().expect("train_step needs a forward pass to learn from");
+        let loss = Loss::default();
+        self.set_optimizer(&Sgd::new(learning_rate));
+
+        let error = self.accumulate_gradients(&output, target, loss);
+        self.update_emotional_state(&output, target, loss);
+        self.apply_gradients(learning_rate, 0.0, None);
+        self.adapt_architecture();
+        error
+    }
+
+    /// Computes the same output as [`NeuroForge::forward`] without changing any
+    /// state: quantum phases, activation histories, emotional memory and the
+    /// random stream are the same afterwards as before. Panics if `input` does
+    /// not fit the first layer.
+    pub fn evaluate(&self, input: &[f64], time: f64) -> Vec<f64> {
+        if let Some(expected) = self.input_size() {
+            if input.len() != expected {
+                panic!("{}", ForwardError::InputShape { expected, got: input.len() });
+            }
+        }
+
+        let mut layers = self.layers.clone();
+        let mut rng = self.rng.clone();
+        self.forward_detached(&mut layers, &mut rng, input, time)
+    }
+
+    /// Runs the forward pass through `layers` and `rng`, which stand in for the
+    /// network's own so that the caller decides what survives the call.
+    fn forward_detached(&self, layers: &mut [Layer], rng: &mut ChaCha12Rng, input: &[f64], time: f64) -> Vec<f64> {
+        let current_input = run_with_skips(&self.skip_connections, layers.len(), input.to_vec(), |l, x| {
+            layers[l].forward(x, self.emotional_state, time, rng)
+        });
+        self.neuro_symbolic_layer.evaluate(self.output_activation.apply(&current_input))
+    }
+
+    /// Runs [`NeuroForge::forward`] on every input in order, panicking if any
+    /// input does not fit the first layer.
+    ///
+    /// Quantum phases and emotional memory carry over from one sample to the
+    /// next exactly as with repeated `forward` calls. When every layer is a
+    /// quantum layer, each layer is instead evaluated for the whole batch with a
+    /// single matrix product before moving to the next layer. Each neuron still
+    /// sees the samples in order, so this gives the same result as the
+    /// per-sample loop in [`QuantumMode::Deterministic`]; in stochastic mode only
+    /// the order of random draws differs. A frozen network always runs the
+    /// per-sample loop, so it never measures or rotates a neuron.
+    pub fn forward_batch(&mut self, inputs: &[Vec<f64>], time: f64) -> Vec<Vec<f64>> {
+        let all_quantum = !self.layers.is_empty() && self.layers.iter().all(|layer| matches!(layer, Layer::Quantum(_)));
+        if self.frozen || !all_quantum || !self.skip_connections.is_empty() || inputs.is_empty() {
+            return inputs.iter().map(|input| self.forward(input, time)).collect();
+        }
+
+        let input_size = self.layers[0].input_size();
+        if let Some(input) = inputs.iter().find(|input| input.len() != input_size) {
+            panic!("{}", ForwardError::InputShape { expected: input_size, got: input.len() });
+        }
+
+        let flat: Vec<f64> = inputs.iter().flatten().copied().collect();
+        let mut batch = Array2::from_shape_vec((inputs.len(), input_size), flat).expect("inputs have equal lengths");
+        for layer in &mut self.layers {
+            if let Layer::Quantum(layer) = layer {
+                batch = layer.forward_batch(&batch, self.emotional_state, &mut self.rng);
+            }
+        }
+
+        batch.outer_iter()
+            .map(|row| self.finish_forward(row.to_vec()))
+            .collect()
+    }
+
+    /// Runs [`NeuroForge::forward`] and returns the index of the largest
+    /// neural output, ignoring any outputs appended by symbolic rules. When
+    /// several outputs share the maximum, the lowest index wins.
+    pub fn predict_class(&mut self, input: &[f64], time: f64) -> usize {
+        let output = self.forward(input, time);
+        let neural = &output[..self.neural_output_size(&output)];
+        let mut best = 0;
+        for (i, &value) in neural.iter().enumerate() {
+            if value > neural[best] {
+                best = i;
+            }
+        }
+        best
+    }
+
+    /// Accuracy of [`NeuroForge::predict_class`] over `inputs` against the
+    /// class labels in `targets`, with every input presented at time 0.
+    pub fn score(&mut self, inputs: &[Vec<f64>], targets: &[usize]) -> f64 {
+        let predictions: Vec<usize> = inputs.iter().map(|input| self.predict_class(input, 0.0)).collect();
+        metrics::accuracy(&predictions, targets)
+    }
+
+    /// Runs [`NeuroForge::forward`] and returns the neural outputs, without
+    /// any symbolic rule outputs, normalized by softmax. A network whose output
+    /// activation is already [`OutputActivation::Softmax`] returns its outputs
+    /// unchanged.
+    pub fn predict_proba(&mut self, input: &[f64], time: f64) -> Vec<f64> {
+        let mut output = self.forward(input, time);
+        output.truncate(self.neural_output_size(&output));
+        match self.output_activation {
+            OutputActivation::Softmax => output,
+            _ => OutputActivation::Softmax.apply(&output),
+        }
+    }
+
+    /// Number of leading elements of a forward `output` that come from the
+    /// last layer rather than from symbolic rules.
+    fn neural_output_size(&self, output: &[f64]) -> usize {
+        self.layers.last().map_or(output.len(), |layer| layer.output_size()).min(output.len())
+    }
+
+    /// Number of inputs the first layer expects, or `None` for a network with no layers.
+    pub fn input_size(&self) -> Option<usize> {
+        self.layers.first().map(|layer| layer.input_size())
+    }
+
+    /// Trains with plain SGD at the given learning rate, returning one
+    /// [`EpochStats`] per epoch.
+    pub fn train(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], epochs: usize, learning_rate: f64) -> Vec<EpochStats> {
+        self.train_with_optimizer(inputs, targets, epochs, &Sgd::new(learning_rate))
+    }
+
+    /// Trains with plain SGD on examples streamed from `data`, which is cloned
+    /// to iterate it again every epoch. Behaves like [`NeuroForge::train`] on
+    /// the same examples with [`TrainConfig::shuffle`] off, without holding
+    /// them all in memory.
+    pub fn train_stream<I>(&mut self, data: I, epochs: usize, learning_rate: f64) -> Vec<EpochStats>
+    where
+        I: Iterator<Item = (Vec<f64>, Vec<f64>)> + Clone,
+    {
+        let optimizer = Sgd::new(learning_rate);
+        let config = TrainConfig { epochs, shuffle: false, ..TrainConfig::default() };
+        self.set_optimizer(&optimizer);
+        (0..epochs).map(|epoch| self.train_epoch_on(data.clone().map(|(x, y)| (x, y, 1.0)), &optimizer, &config, epoch)).collect()
+    }
+
+    /// Trains with plain SGD, scaling each example's error signal, and its
+    /// share of the reported mean error, by its entry in `sample_weights`. An
+    /// example with weight 0 does not move the weights. Panics unless there is
+    /// one weight per input.
+    pub fn train_weighted(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        sample_weights: &[f64],
+        epochs: usize,
+        learning_rate: f64,
+    ) -> Vec<EpochStats> {
+        assert_eq!(sample_weights.len(), inputs.len(), "expected one sample weight per input");
+        let optimizer = Sgd::new(learning_rate);
+        let config = TrainConfig { epochs, ..TrainConfig::default() };
+        self.set_optimizer(&optimizer);
+        (0..epochs)
+            .map(|epoch| self.train_weighted_epoch(inputs, targets, Some(sample_weights), &optimizer, &config, epoch))
+            .collect()
+    }
+
+    /// Trains with plain SGD, giving each layer family its own learning rate.
+    pub fn train_with_rates(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], epochs: usize, learning_rates: LearningRates) -> Vec<EpochStats> {
+        let config = TrainConfig { epochs, learning_rates: Some(learning_rates), ..TrainConfig::default() };
+        self.train_with_config(inputs, targets, &Sgd::new(learning_rates.quantum), &config)
+    }
+
+    /// Trains using `optimizer` for the weight updates. Every layer receives its
+    /// own fresh instance, whose state then persists for the whole run.
+    pub fn train_with_optimizer(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], epochs: usize, optimizer: &dyn Optimizer) -> Vec<EpochStats> {
+        self.train_with_config(inputs, targets, optimizer, &TrainConfig { epochs, ..TrainConfig::default() })
+    }
+
+    /// Trains with plain SGD, applying one averaged update per `batch_size`
+    /// examples. A final partial batch is applied as well. Architecture adaptation
+    /// runs once per batch, after the update.
+    pub fn train_batched(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], epochs: usize, learning_rate: f64, batch_size: usize) -> Vec<EpochStats> {
+        let config = TrainConfig { epochs, batch_size, ..TrainConfig::default() };
+        self.train_with_config(inputs, targets, &Sgd::new(learning_rate), &config)
+    }
+
+    /// Trains with full control over the run through `config`. Every layer
+    /// receives its own fresh instance of `optimizer`.
+    ///
+    /// Panics if `config.validate_inputs` is set and the data holds a non-finite
+    /// value; use [`NeuroForge::try_train_with_config`] to handle that case.
+    pub fn train_with_config(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], optimizer: &dyn Optimizer, config: &TrainConfig) -> Vec<EpochStats> {
+        self.try_train_with_config(inputs, targets, optimizer, config).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [`NeuroForge::train_with_config`], but reports invalid data as an
+    /// error instead of panicking. Nothing is trained when an error is returned.
+    pub fn try_train_with_config(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        optimizer: &dyn Optimizer,
+        config: &TrainConfig,
+    ) -> Result<Vec<EpochStats>, TrainError> {
+        assert!(config.batch_size > 0, "batch_size must be at least 1");
+        if config.validate_inputs {
+            validate_finite(inputs, targets)?;
+        }
+        self.set_optimizer(optimizer);
+
+        Ok((0..config.epochs).map(|epoch| self.train_epoch(inputs, targets, optimizer, config, epoch)).collect())
+    }
+
+    /// Trains with plain SGD until the mean epoch error has not improved for
+    /// `patience` consecutive epochs, or `max_epochs` is reached. The layer
+    /// and symbolic rule weights from the best epoch are restored before
+    /// returning.
+    pub fn train_with_early_stopping(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        max_epochs: usize,
+        learning_rate: f64,
+        patience: usize,
+    ) -> EarlyStoppingReport {
+        let optimizer = Sgd::new(learning_rate);
+        let config = TrainConfig { epochs: max_epochs, ..TrainConfig::default() };
+        self.set_optimizer(&optimizer);
+
+        let mut report = EarlyStoppingReport {
+            stopped_epoch: max_epochs.saturating_sub(1),
+            best_epoch: 0,
+            best_error: f64::INFINITY,
+            triggered: false,
+        };
+        let mut best_weights = None;
+        let mut epochs_without_improvement = 0;
+
+        for epoch in 0..max_epochs {
+            let error = self.train_epoch(inputs, targets, &optimizer, &config, epoch).mean_error;
+            if error < report.best_error - EARLY_STOPPING_TOLERANCE {
+                report.best_error = error;
+                report.best_epoch = epoch;
+                best_weights = Some((self.layers.clone(), self.rule_weights()));
+                epochs_without_improvement = 0;
+            } else {
+                epochs_without_improvement += 1;
+                if epochs_without_improvement >= patience {
+                    report.stopped_epoch = epoch;
+                    report.triggered = true;
+                    break;
+                }
+            }
+        }
+
+        if let Some((layers, rule_weights)) = best_weights {
+            self.layers = layers;
+            self.set_rule_weights(&rule_weights);
+        }
+        report
+    }
+
+    /// Trains with plain SGD, calling `callback` with the stats of every
+    /// finished epoch. Returning [`ControlFlow::Break`] from the callback stops
+    /// training after that epoch. Returns the stats of the epochs that ran.
+    pub fn train_with_callback(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        epochs: usize,
+        learning_rate: f64,
+        mut callback: impl FnMut(&EpochStats) -> ControlFlow<()>,
+    ) -> Vec<EpochStats> {
+        let optimizer = Sgd::new(learning_rate);
+        let config = TrainConfig { epochs, ..TrainConfig::default() };
+        self.set_optimizer(&optimizer);
+
+        let mut history = Vec::with_capacity(epochs);
+        for epoch in 0..epochs {
+            let stats = self.train_epoch(inputs, targets, &optimizer, &config, epoch);
+            let flow = callback(&stats);
+            history.push(stats);
+            if flow.is_break() {
+                break;
+            }
+        }
+        history
+    }
+
+    /// Trains with plain SGD like [`NeuroForge::train`], scoring the network on
+    /// the validation set with [`NeuroForge::validation_error`] after every
+    /// epoch.
+    pub fn train_with_validation(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        val_inputs: &[Vec<f64>],
+        val_targets: &[Vec<f64>],
+        epochs: usize,
+        learning_rate: f64,
+    ) -> Vec<EpochStats> {
+        let optimizer = Sgd::new(learning_rate);
+        let config = TrainConfig { epochs, ..TrainConfig::default() };
+        self.set_optimizer(&optimizer);
+
+        (0..epochs)
+            .map(|epoch| {
+                let mut stats = self.train_epoch(inputs, targets, &optimizer, &config, epoch);
+                stats.validation_error = Some(self.validation_error(val_inputs, val_targets));
+                stats
+            })
+            .collect()
+    }
+
+    /// Mean loss over `inputs` and `targets` without touching the network.
+    ///
+    /// The samples run through copies of the layers and random generator with
+    /// dropout disabled, so quantum phases, activation histories, emotional
+    /// memory and the training random stream are all left as they were.
+    pub fn validation_error(&self, inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> f64 {
+        let loss = Loss::default();
+        let mut layers = self.layers.clone();
+        for layer in &mut layers {
+            if let Layer::Adaptive(layer) = layer {
+                layer.set_training(false);
+            }
+        }
+        let mut rng = self.rng.clone();
+
+        let total_error: f64 = inputs.iter()
+            .zip(targets.iter())
+            .map(|(input, target)| loss.value(&self.forward_detached(&mut layers, &mut rng, input, 0.0), target))
+            .sum();
+        total_error / inputs.len() as f64
+    }
+
+    /// Runs a single epoch and returns its stats.
+    fn train_epoch(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], optimizer: &dyn Optimizer, config: &TrainConfig, epoch: usize) -> EpochStats {
+        self.train_weighted_epoch(inputs, targets, None, optimizer, config, epoch)
+    }
+
+    /// Runs a single epoch in which example `i` counts `sample_weights[i]`
+    /// times, or once each without weights, and returns its stats.
+    fn train_weighted_epoch(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        sample_weights: Option<&[f64]>,
+        optimizer: &dyn Optimizer,
+        config: &TrainConfig,
+        epoch: usize,
+    ) -> EpochStats {
+        let example = |i: usize| (&inputs[i], &targets[i], sample_weights.map_or(1.0, |weights| weights[i]));
+        let mut order: Vec<usize> = (0..inputs.len().min(targets.len())).collect();
+        if config.shuffle {
+            // One permutation of the indices keeps every input with its target.
+            order.shuffle(&mut self.rng);
+        }
+        self.train_epoch_on(order.into_iter().map(example), optimizer, config, epoch)
+    }
+
+    /// Runs a single epoch over `(input, target, weight)` examples and returns
+    /// its stats. Gradients are applied after every `config.batch_size`
+    /// examples and after the last one.
+    fn train_epoch_on<I, X, Y>(&mut self, examples: I, optimizer: &dyn Optimizer, config: &TrainConfig, epoch: usize) -> EpochStats
+    where
+        I: Iterator<Item = (X, Y, f64)>,
+        X: AsRef<[f64]>,
+        Y: AsRef<[f64]>,
+    {
+        assert!(!self.frozen, "cannot train a frozen network; call unfreeze first");
+        config.loss.assert_valid();
+        let learning_rate = match config.lr_schedule {
+            Some(schedule) => schedule.learning_rate(epoch),
+            None => optimizer.learning_rate(),
+        };
+        let learning_rates = match (config.learning_rates, config.lr_schedule) {
+            (Some(rates), Some(schedule)) => rates.scaled(schedule.factor(epoch)),
+            (Some(rates), None) => rates,
+            (None, _) => LearningRates::uniform(learning_rate),
+        };
+        self.set_learning_rates(&learning_rates);
+
+        let mut total_error = 0.0;
+        let mut count = 0;
+        for (input, target, weight) in examples {
+            let (input, target) = (input.as_ref(), target.as_ref());
+            // Every example is presented at time 0, so a firing from the one
+            // before would otherwise keep the neuron refractory for good.
+            self.clear_refractory();
+            let output = self.forward(input, 0.0);
+            total_error += self.accumulate_gradients_with(&output, target, config.loss, weight, vec![None; self.layers.len()]);
+            self.update_emotional_state(&output, target, config.loss);
+            count += 1;
+            if count % config.batch_size == 0 {
+                self.apply_gradients(learning_rate, config.l2_lambda, config.clip_grad_norm);
+                self.adapt_architecture();
+            }
+        }
+        if count % config.batch_size != 0 {
+            self.apply_gradients(learning_rate, config.l2_lambda, config.clip_grad_norm);
+            self.adapt_architecture();
+        }
+        self.clear_refractory();
+        if let Some(factor) = config.memory_decay {
+            self.emotional_memory.decay(factor);
+        }
+        let mean_error = if count == 0 { 0.0 } else { total_error / count as f64 };
+        if config.verbose {
+            if config.l2_lambda > 0.0 {
+                println!(
+                    "Epoch {}: lr = {}, error = {}, l2 penalty = {}",
+                    epoch, learning_rate, mean_error, self.l2_penalty(config.l2_lambda)
+                );
+            } else {
+                println!("Epoch {}: lr = {}, error = {}", epoch, learning_rate, mean_error);
+            }
+        }
+
+        EpochStats {
+            epoch,
+            mean_error,
+            emotional_state: self.emotional_state,
+            neuron_count: self.layers.iter().map(|layer| layer.output_size()).sum(),
+            validation_error: None,
+        }
+    }
+
+    /// Checks every layer's weights, delays and phases, returning a description
+    /// such as `layer 1 (temporal) neuron 0 delay 2` for each non-finite value.
+    pub fn check_finite(&self) -> Result<(), Vec<String>> {
+        let found: Vec<String> = self.layers.iter().enumerate()
+            .flat_map(|(i, layer)| {
+                layer.non_finite_parameters()
+                    .into_iter()
+                    .map(move |parameter| format!("layer {} ({}) {}", i, layer.kind(), parameter))
+            })
+            .collect();
+
+        if found.is_empty() {
+            Ok(())
+        } else {
+            Err(found)
+        }
+    }
+
+    /// Captures the network's numeric state so it can be brought back with
+    /// [`NeuroForge::restore`], e.g. to keep the best weights seen in training.
+    pub fn snapshot(&self) -> NeuroForgeSnapshot {
+        NeuroForgeSnapshot {
+            layers: self.layers.clone(),
+            heads: self.heads.clone(),
+            rule_weights: self.rule_weights(),
+            emotional_memory: self.emotional_memory.clone(),
+            emotional_state: self.emotional_state,
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Writes the state captured by [`NeuroForge::snapshot`] back into the
+    /// network. Rule weights are restored for the rules still registered
+    /// under the same name. The symbolic rules, output activation and
+    /// emotional dynamics are left as they are.
+    pub fn restore(&mut self, snapshot: &NeuroForgeSnapshot) {
+        self.layers = snapshot.layers.clone();
+        self.heads = snapshot.heads.clone();
+        self.set_rule_weights(&snapshot.rule_weights);
+        self.emotional_memory = snapshot.emotional_memory.clone();
+        self.emotional_state = snapshot.emotional_state;
+        self.rng = snapshot.rng.clone();
+    }
+
+    fn rule_weights(&self) -> Vec<(String, f64)> {
+        self.neuro_symbolic_layer.rule_names().into_iter()
+            .filter_map(|name| Some((name.to_string(), self.neuro_symbolic_layer.rule_weight(name)?)))
+            .collect()
+    }
+
+    fn set_rule_weights(&mut self, rule_weights: &[(String, f64)]) {
+        for (name, weight) in rule_weights {
+            self.neuro_symbolic_layer.set_rule_weight(name, *weight);
+        }
+    }
+
+    /// Phase of every quantum neuron, grouped by quantum layer in declaration order.
+    pub fn quantum_phases(&self) -> Vec<Vec<f64>> {
+        self.layers.iter()
+            .filter_map(|layer| match layer {
+                Layer::Quantum(layer) => Some(layer.neurons.iter().map(|neuron| neuron.phase()).collect()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns every quantum neuron to a phase of zero, leaving all weights untouched.
+    pub fn reset_quantum_phases(&mut self) {
+        for layer in &mut self.layers {
+            if let Layer::Quantum(layer) = layer {
+                for neuron in &mut layer.neurons {
+                    neuron.reset_phase();
+                }
+            }
+        }
+    }
+
+    /// Clears the activation history of every temporal neuron. Call it between
+    /// independent sequences so one does not leak into the next.
+    pub fn reset_temporal_state(&mut self) {
+        for layer in &mut self.layers {
+            if let Layer::Temporal(layer) = layer {
+                layer.reset_history();
+            }
+        }
+    }
+
+    fn clear_refractory(&mut self) {
+        for layer in &mut self.layers {
+            if let Layer::Temporal(layer) = layer {
+                layer.clear_refractory();
+            }
+        }
+    }
+
+    /// Clears all state carried from one input to the next, leaving the learned
+    /// parameters untouched: temporal and adaptive activation histories,
+    /// quantum phases and pending entangled collapses, and the output
+    /// [`NeuroForge::train_step`] would learn from. Emotional memory is kept.
+    pub fn reset_state(&mut self) {
+        self.reset_temporal_state();
+        self.reset_quantum_phases();
+        for layer in &mut self.layers {
+            if let Layer::Adaptive(layer) = layer {
+                layer.reset_history();
+            }
+        }
+        self.last_output = None;
+    }
+
+    /// L2 regularization penalty `l2_lambda / 2 * sum(w^2)` over all layer weights.
+    pub fn l2_penalty(&self, l2_lambda: f64) -> f64 {
+        0.5 * l2_lambda * self.layers.iter().map(|layer| layer.squared_weight_sum()).sum::<f64>()
+    }
+
+    fn set_optimizer(&mut self, optimizer: &dyn Optimizer) {
+        for layer in &mut self.layers {
+            layer.set_optimizer(optimizer.fresh());
+        }
+    }
+
+    fn set_learning_rates(&mut self, learning_rates: &LearningRates) {
+        for layer in &mut self.layers {
+            layer.set_learning_rates(learning_rates);
+        }
+    }
+
+    /// Runs the backward chain for the last forward pass, whose result was
+    /// `output`, accumulating gradients in every layer without applying them.
+    /// Returns the loss of `output` against `target`.
+    fn accumulate_gradients(&mut self, output: &[f64], target: &[f64], loss: Loss) -> f64 {
+        self.accumulate_gradients_with(output, target, loss, 1.0, vec![None; self.layers.len()])
+    }
+
+    /// [`NeuroForge::accumulate_gradients`] for an example counting `weight`
+    /// times, which scales both its error signal and the returned loss, with
+    /// extra error already waiting at the outputs of some layers, indexed by
+    /// layer, which joins the backward chain when it reaches them.
+    fn accumulate_gradients_with(
+        &mut self,
+        output: &[f64],
+        target: &[f64],
+        loss: Loss,
+        weight: f64,
+        mut pending_errors: Vec<Option<Vec<f64>>>,
+    ) -> f64 {
+        let output_error: Vec<f64> = loss.gradient(output, target).into_iter().map(|g| weight * g).collect();
+        let neural_error = self.neuro_symbolic_layer.backward(&output_error);
+
+        // The leading outputs are the activated neural outputs; any after them
+        // were appended by symbolic rules.
+        let activated = &output[..neural_error.len()];
+        let n = output.len().min(target.len());
+        let mut current_error = match self.output_activation.fused_gradient(loss, activated, target, n) {
+            Some(mut error) => {
+                error.iter_mut().for_each(|e| *e *= weight);
+                // The fused form replaces only the loss's direct path; error
+                // routed back through symbolic rules still takes the chain rule.
+                let rule_error: Vec<f64> = neural_error.iter().zip(output_error.iter()).map(|(total, direct)| total - direct).collect();
+                for (e, r) in error.iter_mut().zip(self.output_activation.backward(activated, &rule_error)) {
+                    *e += r;
+                }
+                error
+            }
+            None => self.output_activation.backward(activated, &neural_error),
+        };
+
+        // Error arriving at a layer's output through skip connections or
+        // heads, added once the backward chain reaches that layer.
+        for (l, layer) in self.layers.iter_mut().enumerate().rev() {
+            if let Some(pending_error) = pending_errors[l].take() {
+                add_into(&mut current_error, &pending_error);
+            }
+            current_error = layer.accumulate_gradients(&current_error);
+            for &(from, _) in self.skip_connections.iter().filter(|&&(_, to)| to == l) {
+                match &mut pending_errors[from] {
+                    Some(pending) => add_into(pending, &current_error),
+                    pending => *pending = Some(current_error.clone()),
+                }
+            }
+        }
+
+        weight * loss.value(output, target)
+    }
+
+    fn apply_gradients(&mut self, learning_rate: f64, l2_lambda: f64, max_grad_norm: Option<f64>) {
+        for (l, layer) in self.layers.iter_mut().enumerate() {
+            if self.frozen_layers.contains(&l) {
+                layer.clear_gradients();
+            } else {
+                layer.apply_gradients(l2_lambda, max_grad_norm);
+            }
+        }
+        self.neuro_symbolic_layer.apply_gradients(learning_rate);
+    }
+
+    /// Compares the analytic weight gradients of the backward pass against
+    /// central finite differences of the MSE loss for one example, returning the
+    /// largest relative discrepancy `|analytic - numeric| / max(|analytic|, |numeric|)`.
+    ///
+    /// Every evaluation runs on copies of the layers and random generator, so
+    /// the network's weights, phases and random stream are left as they were.
+    /// The forward passes use `time` 0, as during training.
+    pub fn check_gradients(&mut self, input: &[f64], target: &[f64], epsilon: f64) -> f64 {
+        let loss = Loss::default();
+
+        let mut layers = self.layers.clone();
+        let mut rng = self.rng.clone();
+        let current_input = run_with_skips(&self.skip_connections, layers.len(), input.to_vec(), |l, x| {
+            layers[l].forward(x, self.emotional_state, 0.0, &mut rng)
+        });
+        let output = self.neuro_symbolic_layer.process(self.output_activation.apply(&current_input));
+        let original = std::mem::replace(&mut self.layers, layers);
+        self.accumulate_gradients(&output, target, loss);
+        self.neuro_symbolic_layer.clear_gradients();
+        let analytic = std::mem::replace(&mut self.layers, original);
+
+        let mut worst: f64 = 0.0;
+        for (l, analytic_layer) in analytic.iter().enumerate() {
+            for index in 0..analytic_layer.weight_count() {
+                let loss_at = |offset: f64| {
+                    let mut layers = self.layers.clone();
+                    *layers[l].weight_mut(index) += offset;
+                    let output = self.forward_detached(&mut layers, &mut self.rng.clone(), input, 0.0);
+                    loss.value(&output, target)
+                };
+                let numeric = (loss_at(epsilon) - loss_at(-epsilon)) / (2.0 * epsilon);
+                let gradient = analytic_layer.weight_gradient(index);
+                let scale = gradient.abs().max(numeric.abs());
+                if scale > 0.0 {
+                    worst = worst.max((gradient - numeric).abs() / scale);
+                }
+            }
+        }
+        worst
+    }
+
+    /// Entangles pairs of neurons within the quantum layer at index `layer`, so
+    /// that a neuron's collapse sets its partner's starting state for the next
+    /// forward pass; see [`QuantumNeuron`]. Panics if `layer` is not a quantum
+    /// layer or a neuron index is out of range.
+    pub fn entangle_pairs(&mut self, layer: usize, pairs: &[(usize, usize)]) {
+        match &mut self.layers[layer] {
+            Layer::Quantum(quantum) => quantum.entangle_pairs(pairs),
+            _ => panic!("layer {} is not a quantum layer", layer),
+        }
+    }
+
+    /// Scales how strongly the emotional state drives measurement in the
+    /// quantum layer at index `layer`; see
+    /// [`QuantumNeuron::set_superposition_sensitivity`]. Panics if `layer` is
+    /// not a quantum layer.
+    pub fn set_superposition_sensitivity(&mut self, layer: usize, sensitivity: f64) {
+        match &mut self.layers[layer] {
+            Layer::Quantum(quantum) => {
+                for neuron in &mut quantum.neurons {
+                    neuron.set_superposition_sensitivity(sensitivity);
+                }
+            }
+            _ => panic!("layer {} is not a quantum layer", layer),
+        }
+    }
+
+    /// Sets the superposed output mix of every neuron in the quantum layer at
+    /// index `layer`, and whether training learns it; see
+    /// [`QuantumNeuron::set_mix`]. Panics if `layer` is not a quantum layer.
+    pub fn set_quantum_mix(&mut self, layer: usize, mix: f64, learn: bool) {
+        match &mut self.layers[layer] {
+            Layer::Quantum(quantum) => {
+                for neuron in &mut quantum.neurons {
+                    neuron.set_mix(mix);
+                    neuron.set_learn_mix(learn);
+                }
+            }
+            _ => panic!("layer {} is not a quantum layer", layer),
+        }
+    }
+
+    /// Consumes the network, keeping only what prediction needs as an
+    /// [`InferenceModel`] that can be shared across threads. Its predictions
+    /// match [`NeuroForge::forward`] on a frozen network at time 0, without the
+    /// symbolic rule outputs.
+    pub fn into_inference(self) -> InferenceModel {
+        InferenceModel {
+            input_size: self.input_size(),
+            layers: self.layers.iter().map(Layer::to_inference).collect(),
+            skip_connections: self.skip_connections,
+            output_activation: self.output_activation,
+        }
+    }
+
+    /// Int8 copy of the network for inference on constrained targets; see
+    /// [`QuantizedModel`]. Its predictions approximate
+    /// [`NeuroForge::into_inference`]'s, without the symbolic rule outputs.
+    pub fn quantize(&self) -> QuantizedModel {
+        QuantizedModel {
+            input_size: self.input_size(),
+            layers: self.layers.iter().map(|layer| layer.to_inference().into()).collect(),
+            skip_connections: self.skip_connections.clone(),
+            output_activation: self.output_activation,
+        }
+    }
+
+    /// Adds the output of layer `from` to the input of layer `to` on every
+    /// forward pass, and routes the matching error back to layer `from` during
+    /// training, so gradients can bypass the layers in between.
+    ///
+    /// `from` must come before `to`, and layer `from`'s output must have the
+    /// width of layer `to`'s input. Adaptive layers that resize afterwards
+    /// must keep the widths matching; forward panics otherwise.
+    pub fn add_skip_connection(&mut self, from: usize, to: usize) -> Result<(), BuildError> {
+        if from >= to || to >= self.layers.len() {
+            return Err(BuildError::InvalidSkip { from, to, layers: self.layers.len() });
+        }
+        let (got, expected) = (self.layers[from].output_size(), self.layers[to].input_size());
+        if got != expected {
+            return Err(BuildError::IncompatibleSkip { from, to, expected, got });
+        }
+        if !self.skip_connections.contains(&(from, to)) {
+            self.skip_connections.push((from, to));
+        }
+        Ok(())
+    }
+
+    /// The `(from, to)` pairs added with [`NeuroForge::add_skip_connection`].
+    pub fn skip_connections(&self) -> &[(usize, usize)] {
+        &self.skip_connections
+    }
+
+    /// Type and current size of every layer, in order, e.g. to log how
+    /// architecture adaptation changes the network across epochs.
+    pub fn architecture_summary(&self) -> Vec<LayerInfo> {
+        self.layers.iter()
+            .map(|layer| LayerInfo { kind: layer.kind(), input_size: layer.input_size(), neuron_count: layer.output_size() })
+            .collect()
+    }
+
+    /// Graphviz DOT description of the layer topology: an input node, then one
+    /// cluster per layer labeled with its type and neuron count, chained by
+    /// edges. Render it with e.g. `dot -Tpng`.
+    pub fn to_dot(&self) -> String {
+        let summary = self.architecture_summary();
+        let mut dot = String::from("digraph neuroforge {\n    rankdir=LR;\n    node [shape=box];\n");
+        let input_size = summary.first().map_or(0, |info| info.input_size);
+        dot.push_str(&format!("    input [label=\"input\\n{input_size} values\"];\n"));
+        for (index, info) in summary.iter().enumerate() {
+            dot.push_str(&format!(
+                "    subgraph cluster_{index} {{\n        label=\"layer {index}: {}\";\n        layer_{index} [label=\"{} neurons\"];\n    }}\n",
+                info.kind, info.neuron_count,
+            ));
+        }
+        let mut previous = String::from("input");
+        for index in 0..summary.len() {
+            dot.push_str(&format!("    {previous} -> layer_{index};\n"));
+            previous = format!("layer_{index}");
+        }
+        for (from, to) in &self.skip_connections {
+            dot.push_str(&format!("    layer_{from} -> layer_{to} [style=dashed];\n"));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// JSON value with every layer's learned parameters, for inspecting them
+    /// outside Rust: `{"layers": [...], "rules": [...]}`. Each layer object has
+    /// a `"type"` tag plus `"weights"` (one row per neuron) and `"bias"`;
+    /// quantum layers add `"phases"` and temporal layers `"delays"` (one row
+    /// per neuron). Rules are listed by name only, and non-finite values
+    /// become `null`.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "layers": self.layers.iter().map(Layer::to_json).collect::<Vec<_>>(),
+            "rules": self.neuro_symbolic_layer.rule_names(),
+        })
+    }
+
+    /// Minimum, maximum, mean, spread and near-zero fraction of each layer's
+    /// weights, in layer order.
+    pub fn weight_stats(&self) -> Vec<WeightStats> {
+        self.layers.iter().enumerate()
+            .map(|(layer_index, layer)| {
+                let weights = layer.weight_values();
+                if weights.is_empty() {
+                    return WeightStats { layer_index, min: 0.0, max: 0.0, mean: 0.0, std: 0.0, frac_near_zero: 0.0 };
+                }
+                let count = weights.len() as f64;
+                let mean = weights.iter().sum::<f64>() / count;
+                let variance = weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / count;
+                WeightStats {
+                    layer_index,
+                    min: weights.iter().copied().fold(f64::INFINITY, f64::min),
+                    max: weights.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                    mean,
+                    std: variance.sqrt(),
+                    frac_near_zero: weights.iter().filter(|w| w.abs() < NEAR_ZERO_WEIGHT).count() as f64 / count,
+                }
+            })
+            .collect()
+    }
+
+    /// `(layer_index, neuron_index)` of every neuron, in any kind of layer,
+    /// whose incoming weights have an L2 norm below `threshold`. Such neurons
+    /// barely respond to their input, so they are candidates for pruning.
+    pub fn dead_neurons(&self, threshold: f64) -> Vec<(usize, usize)> {
+        self.layers.iter().enumerate()
+            .flat_map(|(layer_index, layer)| {
+                layer.neuron_weight_norms()
+                    .into_iter()
+                    .enumerate()
+                    .filter(|&(_, norm)| norm < threshold)
+                    .map(move |(neuron_index, _)| (layer_index, neuron_index))
+            })
+            .collect()
+    }
+
+    /// Sets every weight with a magnitude below `threshold` to zero, in every
+    /// layer that is not frozen, and returns how many non-zero weights that
+    /// zeroed. Biases are left alone. Together with [`NeuroForge::sparsity`]
+    /// this reports how much of the network was pruned; further training can
+    /// move pruned weights away from zero again.
+    pub fn prune_weights(&mut self, threshold: f64) -> usize {
+        let mut pruned = 0;
+        for (l, layer) in self.layers.iter_mut().enumerate() {
+            if self.frozen_layers.contains(&l) {
+                continue;
+            }
+            for index in 0..layer.weight_count() {
+                let weight = layer.weight_mut(index);
+                if *weight != 0.0 && weight.abs() < threshold {
+                    *weight = 0.0;
+                    pruned += 1;
+                }
+            }
+        }
+        pruned
+    }
+
+    /// Fraction of the weights across all layers that are exactly zero, e.g.
+    /// after [`NeuroForge::prune_weights`]. Biases are not counted.
+    pub fn sparsity(&self) -> f64 {
+        let weights: Vec<f64> = self.layers.iter().flat_map(Layer::weight_values).collect();
+        if weights.is_empty() {
+            return 0.0;
+        }
+        weights.iter().filter(|&&w| w == 0.0).count() as f64 / weights.len() as f64
+    }
+
+    /// Removes the neurons of hidden adaptive layers whose importance is below
+    /// `importance_threshold`; see [`AdaptiveLayer::prune`]. The next layer
+    /// drops the matching inputs, so the network still runs and its output
+    /// width is unchanged. The output layer, frozen layers and the layers
+    /// feeding them, and layers whose width a skip connection or head depends
+    /// on are never pruned. Returns
+    /// the `(layer_index, neuron_index)` of every removed neuron, numbered as
+    /// before the removal.
+    pub fn prune_neurons(&mut self, importance_threshold: f64) -> Vec<(usize, usize)> {
+        let mut removed = Vec::new();
+        for l in 0..self.layers.len().saturating_sub(1) {
+            let width_is_shared = self.skip_connections.iter().any(|&(from, to)| from == l || to == l + 1)
+                || self.heads.iter().any(|head| head.branch == l);
+            if width_is_shared || self.frozen_layers.contains(&l) || self.frozen_layers.contains(&(l + 1)) {
+                continue;
+            }
+            let Layer::Adaptive(layer) = &mut self.layers[l] else { continue };
+            let neuron_count = layer.output_size();
+            let pruned = layer.prune(importance_threshold, self.emotional_state);
+            if pruned.is_empty() {
+                continue;
+            }
+            let kept: Vec<usize> = (0..neuron_count).filter(|n| !pruned.contains(n)).collect();
+            self.layers[l + 1].keep_inputs(&kept);
+            removed.extend(pruned.into_iter().map(|n| (l, n)));
+        }
+        removed
+    }
+
+    /// Enables or disables unsupervised STDP in every temporal layer; see
+    /// [`TemporalLayer::set_stdp`].
+    pub fn set_stdp(&mut self, enabled: bool) {
+        for layer in &mut self.layers {
+            if let Layer::Temporal(layer) = layer {
+                layer.set_stdp(enabled);
+            }
+        }
+    }
+
+    /// Sets the dropout rate of every adaptive layer.
+    pub fn set_dropout_rate(&mut self, dropout_rate: f64) {
+        for layer in &mut self.layers {
+            if let Layer::Adaptive(layer) = layer {
+                layer.set_dropout_rate(dropout_rate);
+            }
+        }
+    }
+
+    /// Puts the network in inference mode, disabling dropout.
+    pub fn eval(&mut self) {
+        self.set_training(false);
+    }
+
+    /// Puts the network back in training mode, re-enabling dropout.
+    pub fn train_mode(&mut self) {
+        self.set_training(true);
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in &mut self.layers {
+            if let Layer::Adaptive(layer) = layer {
+                layer.set_training(training);
+            }
+        }
+    }
+
+    /// Puts the network in frozen mode for serving: [`NeuroForge::forward`]
+    /// no longer measures or rotates quantum neurons, applies dropout or STDP,
+    /// or records activation history, so identical inputs give identical
+    /// outputs, and the emotional state is left as it is. Training a frozen
+    /// network panics; call [`NeuroForge::unfreeze`] first. The output of the
+    /// last forward pass is dropped, so `train_step` needs a new one after
+    /// unfreezing.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+        self.last_output = None;
+    }
+
+    /// Leaves frozen mode; see [`NeuroForge::freeze`].
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Holds layer `index`'s weights fixed during training, e.g. to train new
+    /// layers on top of a pretrained trunk. The layer still runs forward and
+    /// passes the error back to the layers before it, but its gradients are
+    /// discarded and, for an adaptive layer, it is neither resized nor
+    /// mutated. STDP, if enabled, still runs in the forward pass. Panics if
+    /// there is no layer `index`.
+    pub fn freeze_layer(&mut self, index: usize) {
+        assert!(index < self.layers.len(), "no layer {} in a network of {} layers", index, self.layers.len());
+        if !self.frozen_layers.contains(&index) {
+            self.frozen_layers.push(index);
+        }
+    }
+
+    /// Lets layer `index` train again; see [`NeuroForge::freeze_layer`].
+    pub fn unfreeze_layer(&mut self, index: usize) {
+        self.frozen_layers.retain(|&l| l != index);
+    }
+
+    pub fn is_layer_frozen(&self, index: usize) -> bool {
+        self.frozen_layers.contains(&index)
+    }
+
+    /// Activation applied to the last layer's outputs before the symbolic rules run.
+    pub fn output_activation(&self) -> OutputActivation {
+        self.output_activation
+    }
+
+    /// Sets the activation applied to the last layer's outputs. Pair
+    /// [`OutputActivation::Softmax`] with [`Loss::CrossEntropy`], or
+    /// [`OutputActivation::Sigmoid`] with [`Loss::BinaryCrossEntropy`], for the
+    /// combined gradient `output - target`.
+    pub fn set_output_activation(&mut self, activation: OutputActivation) {
+        self.output_activation = activation;
+    }
+
+    /// Dynamics of the emotional state; see [`EmotionalConfig`].
+    pub fn emotional_config(&self) -> EmotionalConfig {
+        self.emotional_config
+    }
+
+    /// Current emotional state, updated from the training error.
+    pub fn emotional_state(&self) -> f64 {
+        self.emotional_state
+    }
+
+    /// Recalls the stored output whose emotional intensity is closest to `emotion`.
+    pub fn recall_memory(&self, emotion: f64) -> Option<Vec<f64>> {
+        self.emotional_memory.recall(emotion)
+    }
+
+    /// Blends the `k` stored outputs closest to `emotion`; see [`EmotionalMemory::recall_blend`].
+    pub fn recall_memory_blend(&self, emotion: f64, k: usize) -> Option<Vec<f64>> {
+        self.emotional_memory.recall_blend(emotion, k)
+    }
+
+    /// Forgets stored outputs whose intensity has decayed below `threshold`
+    /// whenever a new one is stored; see [`TrainConfig::memory_decay`].
+    pub fn set_memory_forget_threshold(&mut self, threshold: Option<f64>) {
+        self.emotional_memory.set_forget_threshold(threshold);
+    }
+
+    /// Number of outputs currently held in emotional memory.
+    pub fn memory_len(&self) -> usize {
+        self.emotional_memory.len()
+    }
+
+    /// Writes the full network state (weights, delays, plasticity, quantum phases,
+    /// emotional state and memory) to `path` in bincode format.
+    ///
+    /// Symbolic rules are closures and cannot be serialized; only their names are
+    /// stored. See [`NeuroForge::load`].
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Restores a network previously written with [`NeuroForge::save`].
+    ///
+    /// Symbolic rules come back as no-ops that output `0.0` under their original
+    /// names; re-register them with `add_rule` to restore their behaviour.
+    #[cfg(feature = "serde")]
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn update_emotional_state(&mut self, output: &[f64], target: &[f64], loss: Loss) {
+        let error = loss.value(output, target);
+        self.emotional_state = self.emotional_config.update(self.emotional_state, error);
+    }
+
+    fn adapt_architecture(&mut self) {
+        for (l, layer) in self.layers.iter_mut().enumerate() {
+            if let Layer::Adaptive(layer) = layer {
+                if !self.frozen_layers.contains(&l) {
+                    layer.adapt(self.emotional_state, &mut self.rng);
+                }
+            }
+        }
+    }
+}
+
+impl Layer {
+    fn input_size(&self) -> usize {
+        match self {
+            Layer::Quantum(layer) => layer.input_size(),
+            Layer::Adaptive(layer) => layer.input_size(),
+            Layer::Temporal(layer) => layer.input_size(),
+        }
+    }
+
+    fn to_inference(&self) -> InferenceLayer {
+        match self {
+            Layer::Quantum(layer) => InferenceLayer::Quantum {
+                weights: layer.weights.clone(),
+                bias: layer.bias.clone(),
+                neurons: layer.neurons.clone(),
+            },
+            Layer::Adaptive(layer) => {
+                let (weights, bias, activation) = layer.parameters();
+                InferenceLayer::Adaptive { weights: weights.clone(), bias: bias.clone(), activation }
+            }
+            Layer::Temporal(layer) => {
+                let mut neurons = layer.neurons.clone();
+                for neuron in &mut neurons {
+                    neuron.clear_refractory();
+                }
+                InferenceLayer::Temporal { neurons }
+            }
+        }
+    }
+
+    /// Forward pass that leaves the layer untouched and ignores all randomness.
+    fn infer(&self, input: &[f64], time: f64) -> Vec<f64> {
+        match self {
+            Layer::Quantum(layer) => {
+                let weighted_inputs = layer.weights.dot(&ndarray::aview1(input)) + &layer.bias;
+                layer.neurons.iter().zip(weighted_inputs.iter()).map(|(neuron, &x)| neuron.peek(x)).collect()
+            }
+            Layer::Adaptive(layer) => layer.infer(input),
+            Layer::Temporal(layer) => layer.infer(input, time),
+        }
+    }
+
+    fn forward<R: Rng + ?Sized>(&mut self, input: &[f64], emotional_state: f64, time: f64, rng: &mut R) -> Vec<f64> {
+        match self {
+            Layer::Quantum(layer) => layer.forward(input, emotional_state, rng),
+            Layer::Adaptive(layer) => layer.forward(input, rng),
+            Layer::Temporal(layer) => layer.forward(input, time),
+        }
+    }
+
+    fn accumulate_gradients(&mut self, error: &[f64]) -> Vec<f64> {
+        match self {
+            Layer::Quantum(layer) => layer.accumulate_gradients(error),
+            Layer::Adaptive(layer) => layer.accumulate_gradients(error),
+            Layer::Temporal(layer) => layer.accumulate_gradients(error),
+        }
+    }
+
+    fn apply_gradients(&mut self, l2_lambda: f64, max_grad_norm: Option<f64>) {
+        match self {
+            Layer::Quantum(layer) => layer.apply_gradients(l2_lambda, max_grad_norm),
+            Layer::Adaptive(layer) => layer.apply_gradients(l2_lambda, max_grad_norm),
+            Layer::Temporal(layer) => layer.apply_gradients(l2_lambda, max_grad_norm),
+        }
+    }
+
+    fn clear_gradients(&mut self) {
+        match self {
+            Layer::Quantum(layer) => layer.clear_gradients(),
+            Layer::Adaptive(layer) => layer.clear_gradients(),
+            Layer::Temporal(layer) => layer.clear_gradients(),
+        }
+    }
+
+    fn keep_inputs(&mut self, kept: &[usize]) {
+        match self {
+            Layer::Quantum(layer) => layer.keep_inputs(kept),
+            Layer::Adaptive(layer) => layer.keep_inputs(kept),
+            Layer::Temporal(layer) => layer.keep_inputs(kept),
+        }
+    }
+
+    fn set_learning_rates(&mut self, learning_rates: &LearningRates) {
+        match self {
+            Layer::Quantum(layer) => layer.optimizer.set_learning_rate(learning_rates.quantum),
+            Layer::Adaptive(layer) => layer.set_learning_rate(learning_rates.adaptive),
+            Layer::Temporal(layer) => layer.set_learning_rate(learning_rates.temporal),
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        match self {
+            Layer::Quantum(layer) => layer.neurons.len(),
+            Layer::Adaptive(layer) => layer.output_size(),
+            Layer::Temporal(layer) => layer.neurons.len(),
+        }
+    }
+
+    /// Every weight of the layer, row by row.
+    fn weight_values(&self) -> Vec<f64> {
+        match self {
+            Layer::Quantum(layer) => layer.weights.iter().copied().collect(),
+            Layer::Adaptive(layer) => layer.parameters().0.iter().copied().collect(),
+            Layer::Temporal(layer) => layer.neurons.iter().flat_map(|neuron| neuron.weights().iter().copied()).collect(),
+        }
+    }
+
+    /// L2 norm of each neuron's incoming weights, in neuron order.
+    fn neuron_weight_norms(&self) -> Vec<f64> {
+        match self {
+            Layer::Quantum(layer) => layer.weights.outer_iter().map(|row| row.dot(&row).sqrt()).collect(),
+            Layer::Adaptive(layer) => layer.parameters().0.outer_iter().map(|row| row.dot(&row).sqrt()).collect(),
+            Layer::Temporal(layer) => layer.neurons.iter()
+                .map(|neuron| neuron.weights().iter().map(|w| w * w).sum::<f64>().sqrt())
+                .collect(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> Value {
+        let rows = |weights: &Array2<f64>| weights.outer_iter().map(|row| row.to_vec()).collect::<Vec<_>>();
+        match self {
+            Layer::Quantum(layer) => json!({
+                "type": self.kind(),
+                "weights": rows(&layer.weights),
+                "bias": layer.bias.to_vec(),
+                "phases": layer.neurons.iter().map(QuantumNeuron::phase).collect::<Vec<_>>(),
+            }),
+            Layer::Adaptive(layer) => {
+                let (weights, bias, _) = layer.parameters();
+                json!({ "type": self.kind(), "weights": rows(weights), "bias": bias.to_vec() })
+            }
+            Layer::Temporal(layer) => json!({
+                "type": self.kind(),
+                "weights": layer.neurons.iter().map(|neuron| neuron.weights().to_vec()).collect::<Vec<_>>(),
+                "bias": layer.neurons.iter().map(|neuron| neuron.bias()).collect::<Vec<_>>(),
+                "delays": layer.neurons.iter().map(|neuron| neuron.delays().to_vec()).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Layer::Quantum(_) => "quantum",
+            Layer::Adaptive(_) => "adaptive",
+            Layer::Temporal(_) => "temporal",
+        }
+    }
+
+    fn non_finite_parameters(&self) -> Vec<String> {
+        match self {
+            Layer::Quantum(layer) => layer.non_finite_parameters(),
+            Layer::Adaptive(layer) => layer.non_finite_parameters(),
+            Layer::Temporal(layer) => layer.non_finite_parameters(),
+        }
+    }
+
+    fn squared_weight_sum(&self) -> f64 {
+        match self {
+            Layer::Quantum(layer) => layer.weights.iter().map(|w| w * w).sum(),
+            Layer::Adaptive(layer) => layer.squared_weight_sum(),
+            Layer::Temporal(layer) => layer.squared_weight_sum(),
+        }
+    }
+
+    fn weight_count(&self) -> usize {
+        match self {
+            Layer::Quantum(layer) => layer.weights.len(),
+            Layer::Adaptive(layer) => layer.input_size() * layer.output_size(),
+            Layer::Temporal(layer) => layer.input_size() * layer.neurons.len(),
+        }
+    }
+
+    fn weight_mut(&mut self, index: usize) -> &mut f64 {
+        match self {
+            Layer::Quantum(layer) => {
+                let inputs = layer.weights.ncols();
+                &mut layer.weights[[index / inputs, index % inputs]]
+            }
+            Layer::Adaptive(layer) => layer.weight_mut(index),
+            Layer::Temporal(layer) => layer.weight_mut(index),
+        }
+    }
+
+    fn weight_gradient(&self, index: usize) -> f64 {
+        match self {
+            Layer::Quantum(layer) => layer.gradient_sum.iter().nth(index).copied().unwrap_or(0.0),
+            Layer::Adaptive(layer) => layer.weight_gradient(index),
+            Layer::Temporal(layer) => layer.weight_gradient(index),
+        }
+    }
+
+    fn set_optimizer(&mut self, optimizer: Box<dyn Optimizer>) {
+        match self {
+            Layer::Quantum(layer) => layer.optimizer = optimizer,
+            Layer::Adaptive(layer) => layer.set_optimizer(optimizer),
+            Layer::Temporal(layer) => layer.set_optimizer(optimizer),
+        }
+    }
+}
+
+impl QuantumLayer {
+    /// A layer of `out_size` neurons, each fed from all `in_size` inputs.
+    fn new<R: Rng + ?Sized>(
+        in_size: usize,
+        out_size: usize,
+        mode: QuantumMode,
+        unit_output: bool,
+        use_bias: bool,
+        init: WeightInit,
+        rng: &mut R,
+    ) -> Self {
+        QuantumLayer {
+            neurons: (0..out_size).map(|_| QuantumNeuron::with_options(mode, unit_output)).collect(),
+            weights: Array::from_shape_fn((out_size, in_size), |_| init.sample(in_size, out_size, rng)),
+            bias: Array1::zeros(out_size),
+            use_bias,
+            entangled_pairs: Vec::new(),
+            last_input: Array1::zeros(in_size),
+            optimizer: default_optimizer(),
+            gradient_sum: Array2::zeros((out_size, in_size)),
+            bias_gradient_sum: Array1::zeros(out_size),
+            mix_gradient_sum: Array1::zeros(out_size),
+            accumulated_steps: 0,
+        }
+    }
+
+    fn forward<R: Rng + ?Sized>(&mut self, input: &[f64], emotional_state: f64, rng: &mut R) -> Vec<f64> {
+        self.last_input = Array1::from_vec(input.to_vec());
+        let weighted_inputs = self.weights.dot(&self.last_input) + &self.bias;
+        
+        #[cfg(not(feature = "rayon"))]
+        let outputs = self.neurons
+            .iter_mut()
+            .zip(weighted_inputs.iter())
+            .map(|(neuron, &input)| neuron.activate(input, emotional_state, rng))
+            .collect();
+
+        // Neurons cannot share `rng` across threads, so each gets its own
+        // generator seeded from it; runs stay reproducible for a given seed.
+        #[cfg(feature = "rayon")]
+        let outputs = {
+            let seeds: Vec<u64> = self.neurons.iter().map(|_| rng.gen()).collect();
+            self.neurons
+                .par_iter_mut()
+                .zip(weighted_inputs.to_vec())
+                .zip(seeds)
+                .map(|((neuron, input), seed)| neuron.activate(input, emotional_state, &mut ChaCha12Rng::seed_from_u64(seed)))
+                .collect()
+        };
+
+        self.share_collapses();
+        outputs
+    }
+
+    /// Entangles each `(a, b)` pair of neuron indices: after every forward
+    /// pass, a neuron that collapsed hands its outcome to its partner, which
+    /// starts its next activation from that basis state. A neuron may belong
+    /// to several pairs. Panics if an index is out of range.
+    fn entangle_pairs(&mut self, pairs: &[(usize, usize)]) {
+        for &(a, b) in pairs {
+            assert!(a != b, "a neuron cannot be entangled with itself");
+            let (low, high) = (a.min(b), a.max(b));
+            let (head, tail) = self.neurons.split_at_mut(high);
+            head[low].entangle(&mut tail[0]);
+            self.entangled_pairs.push((a, b));
+        }
+    }
+
+    fn share_collapses(&mut self) {
+        for &(a, b) in &self.entangled_pairs {
+            let (from_a, from_b) = (self.neurons[a].last_collapse(), self.neurons[b].last_collapse());
+            if let Some(outcome) = from_a {
+                self.neurons[b].receive_collapse(outcome);
+            }
+            if let Some(outcome) = from_b {
+                self.neurons[a].receive_collapse(outcome);
+            }
+        }
+    }
+
+    /// Forward pass over a `(batch, in)` matrix of inputs, one row per sample,
+    /// feeding samples to each neuron in row order.
+    fn forward_batch<R: Rng + ?Sized>(&mut self, inputs: &Array2<f64>, emotional_state: f64, rng: &mut R) -> Array2<f64> {
+        if let Some(last) = inputs.outer_iter().last() {
+            self.last_input = last.to_owned();
+        }
+        let mut weighted_inputs = inputs.dot(&self.weights.t()) + &self.bias;
+        for mut row in weighted_inputs.outer_iter_mut() {
+            for (neuron, value) in self.neurons.iter_mut().zip(row.iter_mut()) {
+                *value = neuron.activate(*value, emotional_state, rng);
+            }
+            self.share_collapses();
+        }
+        weighted_inputs
+    }
+
+    fn input_size(&self) -> usize {
+        self.weights.shape()[1]
+    }
+
+    fn non_finite_parameters(&self) -> Vec<String> {
+        let mut found: Vec<String> = self.weights.indexed_iter()
+            .filter(|(_, weight)| !weight.is_finite())
+            .map(|((i, j), _)| format!("weight [{}, {}]", i, j))
+            .collect();
+        for (n, neuron) in self.neurons.iter().enumerate() {
+            if !self.bias[n].is_finite() {
+                found.push(format!("neuron {} bias", n));
+            }
+            if !neuron.is_finite() {
+                found.push(format!("neuron {} phase", n));
+            }
+        }
+        found
+    }
+
+    fn accumulate_gradients(&mut self, error: &[f64]) -> Vec<f64> {
+        let mut next_error = vec![0.0; self.weights.shape()[1]];
+        if self.gradient_sum.dim() != self.weights.dim() {
+            self.gradient_sum = Array2::zeros(self.weights.dim());
+            self.bias_gradient_sum = Array1::zeros(self.bias.len());
+            self.mix_gradient_sum = Array1::zeros(self.bias.len());
+        }
+
+        for (i, (neuron, &neuron_error)) in self.neurons.iter_mut()
+            .zip(error.iter()).enumerate() {
+            let gradient = neuron.calculate_gradient(neuron_error);
+            self.mix_gradient_sum[i] += neuron.mix_gradient(neuron_error);
+            if self.use_bias {
+                self.bias_gradient_sum[i] += gradient;
+            }
+            for (j, next) in next_error.iter_mut().enumerate() {
+                self.gradient_sum[[i, j]] += gradient * self.last_input[j];
+                *next += gradient * self.weights[[i, j]];
+            }
+        }
+
+        self.accumulated_steps += 1;
+        next_error
+    }
+
+    fn apply_gradients(&mut self, l2_lambda: f64, max_grad_norm: Option<f64>) {
+        if self.accumulated_steps == 0 {
+            return;
+        }
+
+        // Weights and biases go through the optimizer as one parameter vector,
+        // so that stateful optimizers see a consistent length.
+        let scale = 1.0 / self.accumulated_steps as f64;
+        let mut gradients: Vec<f64> = self.gradient_sum.iter().chain(self.bias_gradient_sum.iter()).map(|&g| g * scale).collect();
+        if let Some(max_norm) = max_grad_norm {
+            clip_grad_norm(&mut gradients, max_norm);
+        }
+        for (gradient, &weight) in gradients.iter_mut().zip(self.weights.iter()) {
+            *gradient += l2_lambda * weight;
+        }
+        let mut parameters: Vec<f64> = self.weights.iter().chain(self.bias.iter()).copied().collect();
+        self.optimizer.step(&mut parameters, &gradients);
+
+        let (weights, bias) = parameters.split_at(self.weights.len());
+        self.weights.iter_mut().zip(weights).for_each(|(w, &updated)| *w = updated);
+        self.bias.iter_mut().zip(bias).for_each(|(b, &updated)| *b = updated);
+
+        // Like temporal delays, mixes take plain gradient descent steps
+        // outside the optimizer.
+        let learning_rate = self.optimizer.learning_rate();
+        for (neuron, &gradient) in self.neurons.iter_mut().zip(self.mix_gradient_sum.iter()) {
+            neuron.update_mix(gradient * scale, learning_rate);
+        }
+
+        self.clear_gradients();
+    }
+
+    fn clear_gradients(&mut self) {
+        self.gradient_sum.fill(0.0);
+        self.bias_gradient_sum.fill(0.0);
+        self.mix_gradient_sum.fill(0.0);
+        self.accumulated_steps = 0;
+    }
+
+    /// Keeps only the inputs listed in `kept`, in that order.
+    fn keep_inputs(&mut self, kept: &[usize]) {
+        let rows: Vec<Option<usize>> = (0..self.weights.nrows()).map(Some).collect();
+        self.optimizer.remap_state(&matrix_state_sources(self.weights.dim(), &rows, kept));
+        self.weights = self.weights.select(Axis(1), kept);
+        self.last_input = Array1::zeros(kept.len());
+        self.clear_gradients();
+    }
+}
+
+fn validate_finite(inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> Result<(), TrainError> {
+    for (sample, input) in inputs.iter().enumerate() {
+        if let Some(index) = input.iter().position(|x| !x.is_finite()) {
+            return Err(TrainError::NonFiniteInput { sample, index });
+        }
+    }
+    for (sample, target) in targets.iter().enumerate() {
+        if let Some(index) = target.iter().position(|x| !x.is_finite()) {
+            return Err(TrainError::NonFiniteTarget { sample, index });
+        }
+    }
+    Ok(())
+}
+
+/// Runs `layer_count` layers in order through `run`, which maps a layer's
+/// index and input to its output. The output of layer `from` is kept and added
+/// to the input of layer `to` for every skip connection.
+pub(crate) fn run_with_skips(
+    skip_connections: &[(usize, usize)],
+    layer_count: usize,
+    input: Vec<f64>,
+    mut run: impl FnMut(usize, &[f64]) -> Vec<f64>,
+) -> Vec<f64> {
+    let mut outputs: Vec<Vec<f64>> = Vec::new();
+    let mut current = input;
+    for l in 0..layer_count {
+        for &(from, _) in skip_connections.iter().filter(|&&(_, to)| to == l) {
+            assert_eq!(
+                outputs[from].len(), current.len(),
+                "skip connection from layer {} to layer {} no longer matches the layer widths", from, l
+            );
+            add_into(&mut current, &outputs[from]);
+        }
+        current = run(l, &current);
+        if !skip_connections.is_empty() {
+            outputs.push(current.clone());
+        }
+    }
+    current
+}
+
+fn add_into(target: &mut [f64], values: &[f64]) {
+    for (t, v) in target.iter_mut().zip(values) {
+        *t += v;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn quantum_layer(network: &NeuroForge, index: usize) -> &QuantumLayer {
+        match &network.layers[index] {
+            Layer::Quantum(layer) => layer,
+            _ => panic!("layer {} is not a quantum layer", index),
+        }
+    }
+
+    #[test]
+    fn test_neuroforge_creation() {
+        let network = NeuroForge::new(&[2, 3, 1], &[false, false, false], &[false, false, false]);
+        assert_eq!(network.layers.len(), 3);
+        assert!(network.layers.iter().all(|layer| matches!(layer, Layer::Quantum(_))));
+    }
+
+    #[test]
+    fn test_try_new_rejects_mismatched_layers() {
+        let err = NeuroForge::try_new(&[2, 3], &[false, true], &[false, false]).err();
+        assert_eq!(err, Some(BuildError::IncompatibleLayers { layer: 1, expected: 3, got: 2 }));
+        assert!(NeuroForge::try_new(&[3, 3], &[false, true], &[false, false]).is_ok());
+    }
+
+    #[test]
+    fn test_seeded_networks_are_identical() {
+        let mut a = NeuroForge::with_seed(&[3, 3, 3], &[false, true, false], &[false, false, true], 42);
+        let mut b = NeuroForge::with_seed(&[3, 3, 3], &[false, true, false], &[false, false, true], 42);
+        assert_eq!(quantum_layer(&a, 0).weights, quantum_layer(&b, 0).weights);
+
+        let input = vec![0.1, 0.2, 0.3];
+        for step in 0..5 {
+            let time = step as f64;
+            assert_eq!(a.forward(&input, time), b.forward(&input, time));
+        }
+    }
+
+    #[test]
+    fn test_seeded_training_runs_are_identical() {
+        let inputs: Vec<Vec<f64>> = (0..4).map(|i| vec![i as f64 / 4.0, 0.5, 1.0 - i as f64 / 4.0]).collect();
+        let targets: Vec<Vec<f64>> = inputs.iter().map(|x| vec![x[2], x[0], 0.5]).collect();
+        let run = || {
+            let mut network = NeuroForge::with_seed(&[3, 3, 3], &[false, false, true], &[false, true, false], 57);
+            let stats = network.train(&inputs, &targets, 10, 0.1);
+            (stats, network.weight_stats(), network.architecture_summary())
+        };
+        // Growth, shrinking and mutation of the adaptive layer, like every
+        // other random choice, draw from the network's seeded generator.
+        assert_eq!(run(), run());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut network = NeuroForge::with_seed(&[3, 3, 3], &[false, true, false], &[false, false, true], 7);
+        let input = vec![0.4, 0.5, 0.6];
+        network.forward(&input, 0.0);
+
+        let path = std::env::temp_dir().join(format!("neuroforge_round_trip_{}.bin", std::process::id()));
+        network.save(&path).unwrap();
+        let mut restored = NeuroForge::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(network.forward(&input, 1.0), restored.forward(&input, 1.0));
+    }
+
+    #[test]
+    fn test_try_forward_rejects_wrong_input_size() {
+        let mut network = NeuroForge::new(&[3, 3], &[false, false], &[false, false]);
+        let result = network.try_forward(&[1.0, 0.0], 0.0);
+        assert_eq!(result, Err(ForwardError::InputShape { expected: 3, got: 2 }));
+    }
+
+    #[test]
+    fn test_train_with_adam() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 3);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        network.train_with_optimizer(&inputs, &targets, 10, &optimizer::Adam::new(0.01));
+        let output = network.forward(&inputs[0], 0.0);
+        assert!(output.iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_train_batched_applies_partial_batch() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 5);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.5, 0.5]];
+        network.train_batched(&inputs, &targets, 1, 0.1, 2);
+        assert_eq!(quantum_layer(&network, 0).accumulated_steps, 0);
+    }
+
+    #[test]
+    fn test_recall_memory() {
+        let mut network = NeuroForge::new(&[2, 2], &[false, false], &[false, false]);
+        assert_eq!(network.memory_len(), 0);
+        assert_eq!(network.recall_memory(network.emotional_state()), None);
+
+        let output = network.forward(&[0.5, 0.5], 0.0);
+        assert_eq!(network.memory_len(), 1);
+        assert_eq!(network.recall_memory(network.emotional_state()), Some(output));
+    }
+
+    #[test]
+    fn test_temporal_kernel_reaches_every_neuron() {
+        let kernel = TemporalKernel::Gaussian { sigma: 2.0 };
+        let network = NeuroForge::with_temporal_kernel(&[2, 2], &[false, false], &[true, true], kernel);
+        for layer in &network.layers {
+            match layer {
+                Layer::Temporal(layer) => assert!(layer.neurons.iter().all(|neuron| neuron.kernel() == kernel)),
+                _ => panic!("expected temporal layers only"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_l2_regularization_shrinks_weights() {
+        let inputs = vec![vec![0.2, 0.8], vec![0.9, 0.1]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let mut plain = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 11);
+        let mut decayed = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 11);
+
+        plain.train_with_config(&inputs, &targets, &Sgd::new(0.1), &TrainConfig { epochs: 20, ..TrainConfig::default() });
+        decayed.train_with_config(&inputs, &targets, &Sgd::new(0.1), &TrainConfig { epochs: 20, l2_lambda: 0.5, ..TrainConfig::default() });
+
+        assert!(decayed.l2_penalty(1.0) < plain.l2_penalty(1.0));
+    }
+
+    #[test]
+    fn test_zero_l2_matches_plain_training() {
+        let inputs = vec![vec![0.2, 0.8], vec![0.9, 0.1]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let mut plain = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 12);
+        let mut zero = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 12);
+
+        plain.train(&inputs, &targets, 5, 0.1);
+        zero.train_with_config(&inputs, &targets, &Sgd::new(0.1), &TrainConfig { epochs: 5, l2_lambda: 0.0, ..TrainConfig::default() });
+
+        assert_eq!(quantum_layer(&plain, 0).weights, quantum_layer(&zero, 0).weights);
+    }
+
+    #[test]
+    fn test_train_with_cross_entropy() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 8);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let config = TrainConfig { epochs: 5, loss: Loss::BinaryCrossEntropy, ..TrainConfig::default() };
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+        assert!(network.emotional_state().is_finite());
+        assert!(network.forward(&inputs[0], 0.0).iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_huber_limits_the_update_from_an_outlier() {
+        let inputs = vec![vec![0.3, 0.6]];
+        let targets = vec![vec![100.0, -100.0]];
+        let update_size = |loss: Loss| {
+            let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 53);
+            let before: Vec<f64> = network.layers.iter().flat_map(Layer::weight_values).collect();
+            let config = TrainConfig { epochs: 1, loss, ..TrainConfig::default() };
+            let stats = network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+            let after: Vec<f64> = network.layers.iter().flat_map(Layer::weight_values).collect();
+            let change: f64 = before.iter().zip(&after).map(|(b, a)| (a - b).abs()).sum();
+            (change, stats[0].mean_error)
+        };
+
+        let (mse_change, mse_error) = update_size(Loss::Mse);
+        let (huber_change, huber_error) = update_size(Loss::Huber { delta: 1.0 });
+        assert!(huber_change > 0.0 && huber_change < mse_change / 10.0, "{huber_change} vs {mse_change}");
+        assert!(huber_error < mse_error);
+    }
+
+    #[test]
+    fn test_zero_weight_example_leaves_the_weights_alone() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 55);
+        let inputs = vec![vec![0.3, 0.6]];
+        let targets = vec![vec![1.0, 0.0]];
+        let before: Vec<f64> = network.layers.iter().flat_map(Layer::weight_values).collect();
+
+        let stats = network.train_weighted(&inputs, &targets, &[0.0], 2, 0.1);
+        assert!(stats.iter().all(|epoch| epoch.mean_error == 0.0));
+        assert_eq!(network.layers.iter().flat_map(Layer::weight_values).collect::<Vec<f64>>(), before);
+
+        network.train_weighted(&inputs, &targets, &[1.0], 1, 0.1);
+        assert_ne!(network.layers.iter().flat_map(Layer::weight_values).collect::<Vec<f64>>(), before);
+    }
 
-impl NeuroForge {
-    pub fn new(layer_sizes: &[usize], adaptive_layers: &[bool], temporal_layers: &[bool]) -> Self {
-        let mut quantum_layers = Vec::new();
-        let mut adaptive_layers_vec = Vec::new();
-        let mut temporal_layers_vec = Vec::new();
-
-        for (&size, (&is_adaptive, &is_temporal)) in layer_sizes.iter().zip(adaptive_layers.iter().zip(temporal_layers.iter())) {
-            if is_adaptive {
-                adaptive_layers_vec.push(AdaptiveLayer::new(size, size * 2, size / 2, 0.1));
-            } else if is_temporal {
-                temporal_layers_vec.push(TemporalLayer::new(size));
-            } else {
-                quantum_layers.push(QuantumLayer::new(size));
+    #[test]
+    fn test_lr_schedule_updates_layer_optimizers() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 9);
+        let inputs = vec![vec![0.0, 1.0]];
+        let targets = vec![vec![1.0, 0.0]];
+        let schedule = LrSchedule::ExponentialDecay { initial: 0.1, gamma: 0.5 };
+        let config = TrainConfig { epochs: 3, lr_schedule: Some(schedule), ..TrainConfig::default() };
+        network.train_with_config(&inputs, &targets, &Sgd::new(1.0), &config);
+        assert_eq!(quantum_layer(&network, 0).optimizer.learning_rate(), schedule.learning_rate(2));
+    }
+
+    #[test]
+    fn test_learning_rates_are_set_per_layer_family() {
+        let mut network = NeuroForge::with_seed(&[2, 2, 2], &[false, true, false], &[false, false, true], 9);
+        let inputs = vec![vec![0.0, 1.0]];
+        let targets = vec![vec![1.0, 0.0]];
+        let rates = LearningRates { quantum: 0.1, adaptive: 0.2, temporal: 0.0 };
+        let before = match &network.layers[2] {
+            Layer::Temporal(layer) => layer.neurons[0].bias(),
+            _ => unreachable!(),
+        };
+        network.train_with_rates(&inputs, &targets, 2, rates);
+
+        assert_eq!(quantum_layer(&network, 0).optimizer.learning_rate(), 0.1);
+        match &network.layers[1] {
+            Layer::Adaptive(layer) => assert_eq!(layer.learning_rate(), 0.2),
+            _ => unreachable!(),
+        }
+        match &network.layers[2] {
+            Layer::Temporal(layer) => {
+                assert_eq!(layer.learning_rate(), 0.0);
+                assert_eq!(layer.neurons[0].bias(), before);
             }
+            _ => unreachable!(),
         }
+    }
 
-        NeuroForge {
-            quantum_layers,
-            adaptive_layers: adaptive_layers_vec,
-            temporal_layers: temporal_layers_vec,
-            emotional_memory: EmotionalMemory::new(100),
-            neuro_symbolic_layer: NeuroSymbolicLayer::new(),
-            emotional_state: 0.5,
+    #[test]
+    fn test_learning_rates_follow_the_schedule() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 9);
+        let schedule = LrSchedule::ExponentialDecay { initial: 0.1, gamma: 0.5 };
+        let rates = LearningRates { quantum: 0.4, adaptive: 0.4, temporal: 0.8 };
+        let config = TrainConfig { epochs: 3, lr_schedule: Some(schedule), learning_rates: Some(rates), ..TrainConfig::default() };
+        network.train_with_config(&[vec![0.0, 1.0]], &[vec![1.0, 0.0]], &Sgd::new(1.0), &config);
+        assert!((quantum_layer(&network, 0).optimizer.learning_rate() - 0.1).abs() < 1e-12);
+        match &network.layers[1] {
+            Layer::Temporal(layer) => assert!((layer.learning_rate() - 0.2).abs() < 1e-12),
+            _ => unreachable!(),
         }
     }
 
-    pub fn forward(&mut self, input: &[f64], time: f64) -> Vec<f64> {
-        let mut current_input = input.to_vec();
+    #[test]
+    fn test_schedule_starting_at_zero_keeps_learning_rates_finite() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 9);
+        let schedule = LrSchedule::Constant(0.0);
+        let rates = LearningRates { quantum: 0.4, adaptive: 0.4, temporal: 0.8 };
+        let config = TrainConfig { epochs: 2, lr_schedule: Some(schedule), learning_rates: Some(rates), ..TrainConfig::default() };
+        network.train_with_config(&[vec![0.0, 1.0]], &[vec![1.0, 0.0]], &Sgd::new(1.0), &config);
+        assert_eq!(quantum_layer(&network, 0).optimizer.learning_rate(), 0.0);
+        assert!(network.check_finite().is_ok());
+    }
 
-        for layer in &mut self.quantum_layers {
-            current_input = layer.forward(&current_input, self.emotional_state);
-        }
+    #[test]
+    fn test_early_stopping_triggers_on_plateau() {
+        // Temporal-only network with a zero learning rate: the error never changes.
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[true, true], 10);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+        let report = network.train_with_early_stopping(&inputs, &targets, 50, 0.0, 3);
+        assert!(report.triggered);
+        assert_eq!(report.best_epoch, 0);
+        assert_eq!(report.stopped_epoch, 3);
+        assert!(report.best_error.is_finite());
+    }
+
+    #[test]
+    fn test_callback_can_stop_training_early() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 10);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+        let mut seen = Vec::new();
+        let history = network.train_with_callback(&inputs, &targets, 10, 0.1, |stats| {
+            seen.push(stats.epoch);
+            if stats.epoch == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!(seen, vec![0, 1, 2]);
+        assert_eq!(history.len(), 3);
+    }
+
+    #[test]
+    fn test_validation_error_leaves_network_untouched() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 12);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+        let history = network.train_with_validation(&inputs, &targets, &inputs, &targets, 3, 0.1);
+        assert!(history.iter().all(|stats| stats.validation_error.is_some_and(f64::is_finite)));
+
+        let phases = network.quantum_phases();
+        let memories = network.emotional_memory.len();
+        let error = network.validation_error(&inputs, &targets);
+        assert_eq!(network.validation_error(&inputs, &targets), error);
+        assert_eq!(network.quantum_phases(), phases);
+        assert_eq!(network.emotional_memory.len(), memories);
+    }
+
+    #[test]
+    fn test_evaluate_matches_forward_without_side_effects() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 14);
+        network.forward(&[0.3, 0.6], 0.0);
+
+        let phases = network.quantum_phases();
+        let memories = network.emotional_memory.len();
+        let evaluated = network.evaluate(&[0.2, 0.9], 1.0);
+        assert_eq!(network.quantum_phases(), phases);
+        assert_eq!(network.emotional_memory.len(), memories);
+        assert_eq!(network.forward(&[0.2, 0.9], 1.0), evaluated);
+    }
+
+    #[test]
+    fn test_early_stopping_not_triggered_within_patience() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[true, true], 10);
+        let inputs = vec![vec![0.0, 1.0]];
+        let targets = vec![vec![1.0, 0.0]];
+
+        let report = network.train_with_early_stopping(&inputs, &targets, 2, 0.0, 5);
+        assert!(!report.triggered);
+        assert_eq!(report.stopped_epoch, 1);
+    }
+
+    #[test]
+    fn test_early_stopping_restores_the_best_rule_weights() {
+        let build = || {
+            let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[true, true], 15);
+            network.neuro_symbolic_layer.add_rule("constant", Box::new(|_: &[f64]| 1.0));
+            network
+        };
+        let inputs = vec![vec![0.4, 0.6]];
+        let targets = vec![vec![1.0, 0.0, 0.25]];
+
+        // A learning rate this large overshoots the rule's target further every
+        // epoch, so the first epoch stays the best one.
+        let mut network = build();
+        let report = network.train_with_early_stopping(&inputs, &targets, 10, 5.0, 2);
+        assert_eq!((report.best_epoch, report.stopped_epoch), (0, 2));
+        let mut best = build();
+        best.train(&inputs, &targets, 1, 5.0);
+        assert_eq!(network.neuro_symbolic_layer.rule_weight("constant"), best.neuro_symbolic_layer.rule_weight("constant"));
+    }
+
+    #[test]
+    fn test_gradient_clipping_keeps_weights_finite() {
+        let inputs = vec![vec![0.5, 0.5], vec![1.0, 0.2]];
+        let targets = vec![vec![1e100, -1e100], vec![-1e100, 1e100]];
+        let config = TrainConfig { epochs: 5, clip_grad_norm: Some(1.0), ..TrainConfig::default() };
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 13);
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+        assert!(network.l2_penalty(1.0).is_finite());
+    }
 
-        for layer in &mut self.adaptive_layers {
-            current_input = layer.forward(&current_input);
+    #[test]
+    fn test_check_finite_reports_layer_and_index() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 14);
+        assert_eq!(network.check_finite(), Ok(()));
+
+        if let Layer::Quantum(layer) = &mut network.layers[0] {
+            layer.weights[[1, 0]] = f64::NAN;
         }
+        if let Layer::Temporal(layer) = &mut network.layers[1] {
+            layer.neurons[0].update_delays(&[0.0, f64::NAN], 1.0);
+        }
+        assert_eq!(
+            network.check_finite(),
+            Err(vec!["layer 0 (quantum) weight [1, 0]".to_string(), "layer 1 (temporal) neuron 0 delay 1".to_string()])
+        );
+    }
 
-        for layer in &mut self.temporal_layers {
-            current_input = layer.forward(&current_input, time);
+    #[test]
+    fn test_validate_inputs_rejects_non_finite_data() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 15);
+        let config = TrainConfig { validate_inputs: true, ..TrainConfig::default() };
+        let inputs = vec![vec![0.5, 0.5], vec![0.1, f64::INFINITY]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+        let result = network.try_train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+        assert_eq!(result, Err(TrainError::NonFiniteInput { sample: 1, index: 1 }));
+        assert_eq!(network.memory_len(), 0);
+
+        let targets = vec![vec![1.0, 0.0], vec![f64::NAN, 1.0]];
+        let result = network.try_train_with_config(&[vec![0.5, 0.5], vec![0.1, 0.2]], &targets, &Sgd::new(0.1), &config);
+        assert_eq!(result, Err(TrainError::NonFiniteTarget { sample: 1, index: 0 }));
+    }
+
+    #[test]
+    fn test_quantum_phases_and_reset() {
+        let mut network = NeuroForge::with_seed(&[2, 2, 2], &[false, false, false], &[false, true, false], 16);
+        assert_eq!(network.quantum_phases(), vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
+
+        network.forward(&[0.3, 0.7], 0.0);
+        assert!(network.quantum_phases().iter().flatten().any(|&phase| phase != 0.0));
+
+        network.reset_quantum_phases();
+        assert_eq!(network.quantum_phases(), vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_deterministic_quantum_forward_is_reproducible() {
+        let mut network = NeuroForge::with_quantum_mode(&[3, 3], &[false, false], &[false, false], QuantumMode::Deterministic);
+        let first = network.forward(&[0.2, 0.4, 0.6], 0.0);
+        for _ in 0..5 {
+            network.reset_quantum_phases();
+            assert_eq!(network.forward(&[0.2, 0.4, 0.6], 0.0), first);
         }
+    }
 
-        current_input = self.neuro_symbolic_layer.process(current_input);
+    #[test]
+    fn test_memory_decay_runs_each_epoch() {
+        let inputs = vec![vec![0.5, 0.5]];
+        let targets = vec![vec![1.0, 0.0]];
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 17);
+        network.set_memory_forget_threshold(Some(1e-3));
 
-        self.emotional_memory.store(current_input.clone(), self.emotional_state);
+        // Without decay every stored output stays above the threshold.
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &TrainConfig { epochs: 5, ..TrainConfig::default() });
+        assert_eq!(network.memory_len(), 5);
 
-        current_input
+        let config = TrainConfig { epochs: 5, memory_decay: Some(0.0), ..TrainConfig::default() };
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+        assert_eq!(network.memory_len(), 1);
     }
 
-    pub fn train(&mut self, inputs: &[Vec<f64>], targets: &[Vec<f64>], epochs: usize, learning_rate: f64) {
-        for epoch in 0..epochs {
-            let mut total_error = 0.0;
-            for (input, target) in inputs.iter().zip(targets.iter()) {
-                let output = self.forward(input, 0.0);
-                total_error += self.backward(target, learning_rate);
-                self.update_emotional_state(&output, target);
-                self.adapt_architecture();
+    #[test]
+    fn test_only_output_adaptive_layer_changes_width() {
+        let inputs = vec![vec![0.5, 0.5], vec![1.0, 0.2]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let mut network = NeuroForge::with_seed(&[2, 2, 2], &[false, true, true], &[false, false, false], 18);
+        network.train(&inputs, &targets, 5, 0.1);
+
+        let widths: Vec<usize> = network.layers.iter()
+            .map(|layer| match layer {
+                Layer::Adaptive(layer) => layer.output_size(),
+                _ => 2,
+            })
+            .collect();
+        assert_eq!(widths[1], 2);
+        assert!(widths[2] > 2);
+        assert_eq!(network.forward(&[0.3, 0.3], 0.0).len(), widths[2]);
+    }
+
+    #[test]
+    fn test_forward_batch_matches_per_sample_forward() {
+        let inputs = vec![vec![0.1, 0.2, 0.3], vec![0.9, -0.4, 0.5], vec![0.0, 0.7, 0.2]];
+        let mut batched = NeuroForge::with_quantum_mode(&[3, 3], &[false, false], &[false, false], QuantumMode::Deterministic);
+        let mut looped = NeuroForge::with_quantum_mode(&[3, 3], &[false, false], &[false, false], QuantumMode::Deterministic);
+        looped.layers = batched.layers.clone();
+
+        let expected: Vec<Vec<f64>> = inputs.iter().map(|input| looped.forward(input, 0.0)).collect();
+        let outputs = batched.forward_batch(&inputs, 0.0);
+        for (output, expected) in outputs.iter().zip(expected.iter()) {
+            for (x, y) in output.iter().zip(expected.iter()) {
+                assert!((x - y).abs() < 1e-12);
             }
-            println!("Epoch {}: error = {}", epoch, total_error / inputs.len() as f64);
         }
+        assert_eq!(batched.memory_len(), 3);
     }
 
-    fn backward(&mut self, target: &[f64], learning_rate: f64) -> f64 {
-        let mut current_error = target.to_vec();
-        let mut total_error = 0.0;
+    #[test]
+    fn test_bias_is_learned_only_when_enabled() {
+        let inputs = vec![vec![0.0, 0.0]];
+        let targets = vec![vec![0.9, 0.9]];
+        let config = TrainConfig { epochs: 3, ..TrainConfig::default() };
+
+        let mut network = NeuroForge::with_bias(&[2, 2], &[false, false], &[false, true], false);
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.5), &config);
+        assert_eq!(quantum_layer(&network, 0).bias, Array1::<f64>::zeros(2));
+        // With zero input and no bias nothing can change the output.
+        let output = network.forward(&[0.0, 0.0], 0.0);
+        assert!(output.iter().all(|&x| (x - 0.5).abs() < 1e-12));
 
-        current_error = self.neuro_symbolic_layer.backward(&current_error);
+        let mut network = NeuroForge::with_bias(&[2, 2], &[false, false], &[false, true], true);
+        network.train_with_config(&inputs, &targets, &Sgd::new(0.5), &config);
+        assert!(quantum_layer(&network, 0).bias.iter().all(|&b| b != 0.0));
+    }
+
+    #[test]
+    fn test_softmax_output_on_three_classes() {
+        let inputs = vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]];
+        let targets = vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]];
+        let mut network = NeuroForge::with_seed(&[3, 3], &[false, false], &[false, true], 21);
+        network.set_output_activation(OutputActivation::Softmax);
+
+        let config = TrainConfig { epochs: 20, loss: Loss::CrossEntropy, ..TrainConfig::default() };
+        let stats = network.train_with_config(&inputs, &targets, &Sgd::new(0.5), &config);
+        assert!(stats.iter().all(|s| s.mean_error.is_finite()));
 
-        for layer in self.temporal_layers.iter_mut().rev() {
-            current_error = layer.backward(&current_error, learning_rate);
+        for input in &inputs {
+            let output = network.forward(input, 0.0);
+            assert_eq!(output.len(), 3);
+            assert!(output.iter().all(|&p| p > 0.0));
+            assert!((output.iter().sum::<f64>() - 1.0).abs() < 1e-12);
         }
+    }
+
+    #[test]
+    fn test_xavier_init_keeps_deep_activations_away_from_saturation() {
+        let sizes = [32; 6];
+        let adaptive = [true; 6];
+        let input: Vec<f64> = (0..32).map(|i| i as f64 / 32.0).collect();
+        let saturation = |init| {
+            let mut network = NeuroForgeBuilder::from_flags(&sizes, &adaptive, &[false; 6]).weight_init(init).seed(5).build().unwrap();
+            let output = network.forward(&input, 0.0);
+            output.iter().map(|y| (y - 0.5).abs()).sum::<f64>() / output.len() as f64
+        };
 
-        for layer in self.adaptive_layers.iter_mut().rev() {
-            current_error = layer.backward(&current_error, learning_rate);
+        assert!(saturation(WeightInit::XavierUniform) < 0.5 * saturation(WeightInit::default()));
+    }
+
+    #[test]
+    fn test_check_gradients_leaves_network_untouched() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 23);
+        network.forward(&[0.1, 0.4], 0.0);
+        let phases = network.quantum_phases();
+        let before = network.evaluate(&[0.3, 0.7], 0.0);
+
+        let discrepancy = network.check_gradients(&[0.3, 0.7], &[1.0, 0.0], 1e-6);
+        assert!(discrepancy.is_finite() && discrepancy >= 0.0);
+        assert_eq!(network.quantum_phases(), phases);
+        assert_eq!(network.evaluate(&[0.3, 0.7], 0.0), before);
+    }
+
+    #[test]
+    fn test_quantum_weight_gradients_match_finite_differences() {
+        // Deterministic mode at the initial emotional state of 0.5 never
+        // collapses, so the output is a smooth function of the weights.
+        for sizes in [&[3][..], &[3, 3]] {
+            let mut builder = NeuroForgeBuilder::new().quantum_mode(QuantumMode::Deterministic).weight_init(WeightInit::XavierUniform).seed(29);
+            for &size in sizes {
+                builder = builder.quantum_layer(size);
+            }
+            let mut network = builder.build().unwrap();
+            network.forward(&[0.2, 0.1, 0.3], 0.0);
+
+            let discrepancy = network.check_gradients(&[0.1, 0.05, 0.2], &[1.0, 0.0, 0.5], 1e-6);
+            assert!(discrepancy < 1e-4, "{:?}: {}", sizes, discrepancy);
         }
+    }
+
+    #[test]
+    fn test_adaptive_and_temporal_weight_gradients_match_finite_differences() {
+        for (adaptive, temporal) in [([true, true], [false, false]), ([false, false], [true, true]), ([false, true], [true, false])] {
+            let mut network = NeuroForgeBuilder::from_flags(&[3, 3], &adaptive, &temporal).quantum_mode(QuantumMode::Deterministic).seed(31).build().unwrap();
 
-        for layer in self.quantum_layers.iter_mut().rev() {
-            current_error = layer.backward(&current_error, learning_rate);
+            let discrepancy = network.check_gradients(&[0.4, -0.3, 0.8], &[1.0, 0.0, 0.5], 1e-6);
+            assert!(discrepancy < 1e-4, "adaptive {:?}, temporal {:?}: {}", adaptive, temporal, discrepancy);
         }
+    }
+
+    #[test]
+    fn test_emotional_state_stays_within_configured_range() {
+        let config = EmotionalConfig { momentum: 0.5, error_gain: 1.0, clamp: (0.1, 0.3) };
+        assert_eq!(config.update(0.2, 0.0), 0.1);
+        assert_eq!(config.update(0.2, 0.1), 0.2);
+        assert_eq!(config.update(0.2, 5.0), 0.3);
+        assert_eq!(EmotionalConfig::default().update(0.5, 0.3), 0.9 * 0.5 + 0.1 * 0.3);
+
+        let mut network = NeuroForge::with_emotional_config(&[2, 2], &[false, false], &[false, true], config);
+        network.train(&[vec![0.0, 1.0]], &[vec![50.0, -50.0]], 3, 0.1);
+        assert_eq!(network.emotional_state(), 0.3);
+    }
+
+    #[test]
+    fn test_restore_returns_to_snapshot() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 33);
+        network.neuro_symbolic_layer.add_rule("positive", Box::new(|output: &[f64]| output[0]));
+        let snapshot = network.snapshot();
+        let expected = network.evaluate(&[0.4, 0.6], 0.0);
 
-        total_error = current_error.iter().map(|&e| e.powi(2)).sum::<f64>() / current_error.len() as f64;
+        network.train(&[vec![0.4, 0.6]], &[vec![1.0, 0.0]], 5, 0.5);
+        assert_ne!(network.evaluate(&[0.4, 0.6], 0.0), expected);
 
-        total_error
+        network.restore(&snapshot);
+        assert_eq!(network.evaluate(&[0.4, 0.6], 0.0), expected);
+        assert_eq!(network.emotional_state(), 0.5);
+        assert_eq!(network.emotional_memory.len(), 0);
+        assert!(network.neuro_symbolic_layer.has_rule("positive"));
     }
 
-    fn update_emotional_state(&mut self, output: &[f64], target: &[f64]) {
-        let error: f64 = output.iter().zip(target.iter()).map(|(o, t)| (o - t).powi(2)).sum::<f64>() / output.len() as f64;
-        self.emotional_state = 0.9 * self.emotional_state + 0.1 * error;
+    #[test]
+    fn test_training_learns_rule_weights() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[true, true], 34);
+        network.neuro_symbolic_layer.add_rule("constant", Box::new(|_: &[f64]| 1.0));
+        let snapshot = network.snapshot();
+
+        // The rule's output has a target of 0.25, so its weight should shrink towards it.
+        network.train(&[vec![0.4, 0.6]], &[vec![1.0, 0.0, 0.25]], 200, 0.5);
+        let weight = network.neuro_symbolic_layer.rule_weight("constant").unwrap();
+        assert!((weight - 0.25).abs() < 0.05, "{}", weight);
+
+        network.restore(&snapshot);
+        assert_eq!(network.neuro_symbolic_layer.rule_weight("constant"), Some(1.0));
     }
 
-    fn adapt_architecture(&mut self) {
-        for layer in &mut self.adaptive_layers {
-            layer.adapt(self.emotional_state);
-        }
+    #[test]
+    fn test_predict_class_and_proba_ignore_rule_outputs() {
+        let mut network = NeuroForgeBuilder::new().quantum_mode(QuantumMode::Deterministic).quantum_layer(3).seed(35).build().unwrap();
+        network.neuro_symbolic_layer.add_rule("large", Box::new(|_: &[f64]| 100.0));
+        let input = [0.1, 0.2, 0.3];
+
+        let output = network.evaluate(&input, 0.0);
+        let expected = (0..3).fold(0, |best, i| if output[i] > output[best] { i } else { best });
+        let snapshot = network.snapshot();
+        assert_eq!(network.predict_class(&input, 0.0), expected);
+
+        network.restore(&snapshot);
+        let proba = network.predict_proba(&input, 0.0);
+        assert_eq!(proba, OutputActivation::Softmax.apply(&output[..3]));
+        assert!((proba.iter().sum::<f64>() - 1.0).abs() < 1e-12);
     }
-}
 
-impl QuantumLayer {
-    fn new(size: usize) -> Self {
-        let mut rng = rand::thread_rng();
-        QuantumLayer {
-            neurons: (0..size).map(|_| QuantumNeuron::new()).collect(),
-            weights: Array::from_shape_fn((size, size), |_| rng.gen_range(-1.0..1.0)),
+    #[test]
+    fn test_score_matches_predict_class() {
+        let mut network = NeuroForge::with_seed(&[2, 3], &[false, false], &[true, true], 21);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![0.5, 0.5]];
+        let predictions: Vec<usize> = inputs.iter().map(|input| network.predict_class(input, 0.0)).collect();
+        let targets = vec![predictions[0], (predictions[1] + 1) % 3, predictions[2]];
+        assert!((network.score(&inputs, &targets) - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_predict_class_breaks_ties_by_lowest_index() {
+        // With zero input and no bias every quantum neuron stays at |0> and outputs 0.
+        let mut network = NeuroForgeBuilder::new().quantum_layer(3).use_bias(false).seed(36).build().unwrap();
+        assert_eq!(network.predict_class(&[0.0, 0.0, 0.0], 0.0), 0);
+    }
+
+    #[test]
+    fn test_frozen_forward_is_repeatable() {
+        let mut network = NeuroForge::with_seed(&[3, 3, 3], &[false, true, false], &[false, false, true], 37);
+        network.set_dropout_rate(0.5);
+        network.set_stdp(true);
+        network.freeze();
+
+        let first = network.forward(&[0.2, 0.5, 0.9], 1.0);
+        for _ in 0..10 {
+            assert_eq!(network.forward(&[0.2, 0.5, 0.9], 1.0), first);
         }
+        assert_eq!(network.emotional_state(), 0.5);
+
+        network.unfreeze();
+        assert!(!network.is_frozen());
+        network.train(&[vec![0.2, 0.5, 0.9]], &[vec![0.5, 0.5, 0.5]], 1, 0.1);
     }
 
-    fn forward(&mut self, input: &[f64], emotional_state: f64) -> Vec<f64> {
-        let input_array = Array1::from_vec(input.to_vec());
-        let weighted_inputs = self.weights.dot(&input_array);
-        
-        self.neurons
-            .iter_mut()
-            .zip(weighted_inputs.iter())
-            .map(|(neuron, &input)| neuron.activate(input, emotional_state))
-            .collect()
+    #[test]
+    fn test_frozen_forward_batch_is_repeatable_and_matches_forward() {
+        let inputs = vec![vec![0.3, 0.8], vec![0.9, 0.1]];
+        let mut network = NeuroForge::with_seed(&[2, 3, 1], &[false, false, false], &[false, false, false], 64);
+        network.freeze();
+
+        let first = network.forward_batch(&inputs, 0.0);
+        assert_eq!(network.forward_batch(&inputs, 0.0), first);
+        let looped: Vec<Vec<f64>> = inputs.iter().map(|input| network.forward(input, 0.0)).collect();
+        assert_eq!(looped, first);
     }
 
-    fn backward(&mut self, error: &[f64], learning_rate: f64) -> Vec<f64> {
-        let mut next_error = vec![0.0; self.weights.shape()[1]];
-        let mut weight_gradients = Array2::zeros(self.weights.dim());
+    #[test]
+    fn test_batched_forward_records_the_output_for_train_step() {
+        let inputs = vec![vec![0.3, 0.8], vec![0.9, 0.1]];
+        let mut network = NeuroForge::with_seed(&[2, 3, 1], &[false, false, false], &[false, false, false], 65);
+        let outputs = network.forward_batch(&inputs, 0.0);
+        let expected = Loss::default().value(&outputs[1], &[1.0]);
+        assert_eq!(network.train_step(&[1.0], 0.1), expected);
+    }
 
-        for (i, (neuron, &neuron_error)) in self.neurons.iter_mut()
-            .zip(error.iter()).enumerate() {
-            let gradient = neuron.calculate_gradient(neuron_error);
-            for j in 0..self.weights.shape()[1] {
-                let input = next_error[j];
-                weight_gradients[[i, j]] = gradient * input;
-                next_error[j] += neuron_error * self.weights[[i, j]];
+    #[test]
+    fn test_train_stream_matches_train() {
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![0.5, 0.5]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.5, 0.5]];
+        let mut sliced = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 39);
+        let mut streamed = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 39);
+
+        let config = TrainConfig { epochs: 4, shuffle: false, ..TrainConfig::default() };
+        let expected = sliced.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+        let stats = streamed.train_stream(inputs.clone().into_iter().zip(targets.clone()), 4, 0.1);
+        assert_eq!(stats, expected);
+        assert_eq!(streamed.evaluate(&inputs[0], 0.0), sliced.evaluate(&inputs[0], 0.0));
+    }
+
+    #[test]
+    fn test_frozen_layer_keeps_its_weights_while_the_others_train() {
+        let mut network = NeuroForgeBuilder::new().quantum_layer(2).temporal_layer(3).quantum_layer(2).seed(51).build().unwrap();
+        let parameter_bits = |layer: &Layer| -> Vec<u64> {
+            match layer {
+                Layer::Temporal(layer) => layer
+                    .neurons
+                    .iter()
+                    .flat_map(|neuron| neuron.weights().iter().chain(neuron.delays()).copied().chain([neuron.bias()]))
+                    .map(f64::to_bits)
+                    .collect(),
+                _ => layer.weight_values().into_iter().map(f64::to_bits).collect(),
             }
-        }
+        };
+        network.freeze_layer(1);
+        assert!(network.is_layer_frozen(1) && !network.is_layer_frozen(0));
+        let before: Vec<Vec<u64>> = network.layers.iter().map(parameter_bits).collect();
 
-        self.weights -= &(weight_gradients * learning_rate);
+        let inputs = vec![vec![0.2, 0.7], vec![0.9, 0.1]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        network.train(&inputs, &targets, 1, 0.1);
+        let after: Vec<Vec<u64>> = network.layers.iter().map(parameter_bits).collect();
+        assert_eq!(after[1], before[1]);
+        // The error still reaches layer 0 through the frozen layer.
+        assert_ne!(after[0], before[0]);
+        assert_ne!(after[2], before[2]);
 
-        next_error
+        network.unfreeze_layer(1);
+        network.train(&inputs, &targets, 1, 0.1);
+        assert_ne!(parameter_bits(&network.layers[1]), before[1]);
+    }
+
+    #[test]
+    fn test_forward_with_activations_returns_every_layer_output() {
+        let build = || {
+            NeuroForgeBuilder::new()
+                .quantum_mode(QuantumMode::Deterministic)
+                .quantum_layer(3)
+                .temporal_layer(4)
+                .quantum_layer(2)
+                .output_activation(OutputActivation::Sigmoid)
+                .seed(49)
+                .build()
+                .unwrap()
+        };
+        let (mut network, mut plain) = (build(), build());
+        let input = [0.1, 0.4, -0.3];
+
+        let (output, activations) = network.forward_with_activations(&input, 0.0);
+        assert_eq!(output, plain.forward(&input, 0.0));
+        assert_eq!(activations.iter().map(Vec::len).collect::<Vec<usize>>(), vec![3, 4, 2]);
+        assert_eq!(OutputActivation::Sigmoid.apply(&activations[2]), output);
+    }
+
+    #[test]
+    fn test_shuffling_is_reproducible_from_the_seed() {
+        let inputs: Vec<Vec<f64>> = (0..6).map(|i| vec![i as f64 / 6.0, 1.0 - i as f64 / 6.0]).collect();
+        let targets: Vec<Vec<f64>> = inputs.iter().map(|x| vec![x[0], x[1]]).collect();
+        let run = |shuffle: bool| {
+            let mut network = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 47);
+            let config = TrainConfig { epochs: 3, shuffle, ..TrainConfig::default() };
+            let stats = network.train_with_config(&inputs, &targets, &Sgd::new(0.1), &config);
+            (stats, network.evaluate(&inputs[0], 0.0))
+        };
+
+        assert!(TrainConfig::default().shuffle);
+        assert_eq!(run(true), run(true));
+        assert_ne!(run(true).1, run(false).1);
+    }
+
+    #[test]
+    fn test_network_with_an_empty_layer_trains_without_panicking() {
+        let mut network = NeuroForgeBuilder::new().quantum_layer(2).temporal_layer(0).seed(43).build().unwrap();
+        assert!(network.forward(&[0.1, 0.2], 0.0).is_empty());
+        let stats = network.train(&[vec![0.1, 0.2]], &[vec![]], 2, 0.1);
+        assert_eq!(stats.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json_exports_parameters_and_rule_names() {
+        let mut network = NeuroForge::with_seed(&[2, 3, 2], &[false, true, false], &[false, false, true], 47);
+        network.neuro_symbolic_layer.add_rule("say \"hi\"", Box::new(|_: &[f64]| 1.0));
+        let Layer::Quantum(quantum) = &mut network.layers[0] else { panic!("expected a quantum layer") };
+        quantum.bias[1] = f64::NAN;
+        let json = network.to_json();
+        let layers = json["layers"].as_array().unwrap();
+        let kinds: Vec<&str> = layers.iter().map(|layer| layer["type"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["quantum", "adaptive", "temporal"]);
+        assert_eq!(layers[0]["phases"], json!([0.0, 0.0]));
+        assert_eq!(layers[0]["weights"][0], json!(quantum_layer(&network, 0).weights.row(0).to_vec()));
+        assert!(layers[0]["bias"][1].is_null());
+        assert_eq!(json["rules"], json!(["say \"hi\""]));
+
+        let Layer::Temporal(layer) = &network.layers[2] else { panic!("expected a temporal layer") };
+        assert_eq!(layers[2]["delays"][0], json!(layer.neurons[0].delays()));
+        assert_eq!(layers[2]["weights"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_weight_stats_summarise_each_layer() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 48);
+        let Layer::Quantum(layer) = &mut network.layers[0] else { panic!("expected a quantum layer") };
+        layer.weights = ndarray::array![[1.0, -1.0], [0.0, 2.0]];
+        let stats = network.weight_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0], WeightStats { layer_index: 0, min: -1.0, max: 2.0, mean: 0.5, std: 1.25_f64.sqrt(), frac_near_zero: 0.25 });
+        assert_eq!(stats[1].layer_index, 1);
+        assert!(stats[1].min <= stats[1].mean && stats[1].mean <= stats[1].max);
+    }
+
+    #[test]
+    fn test_skip_connection_carries_gradient_past_saturated_layer() {
+        let first_layer_gradient = |skip: bool| {
+            let mut network = NeuroForge::with_seed(&[2, 2, 2], &[true, true, true], &[false, false, false], 49);
+            if skip {
+                network.add_skip_connection(0, 2).unwrap();
+            }
+            for index in 0..network.layers[1].weight_count() {
+                *network.layers[1].weight_mut(index) = 50.0;
+            }
+            let output = network.forward(&[1.0, 1.0], 0.0);
+            network.accumulate_gradients(&output, &[0.0, 1.0], Loss::default());
+            network.layers[0].weight_gradient(0)
+        };
+        assert!(first_layer_gradient(false).abs() < 1e-12);
+        assert!(first_layer_gradient(true).abs() > 1e-3);
+    }
+
+    #[test]
+    fn test_skip_connection_gradients_match_finite_differences() {
+        let mut network = NeuroForge::with_seed(&[2, 2, 2], &[true, true, true], &[false, false, false], 50);
+        network.add_skip_connection(0, 2).unwrap();
+        assert!(network.check_gradients(&[0.3, -0.6], &[1.0, 0.0], 1e-6) < 1e-4);
+
+        network.freeze();
+        let expected = network.forward(&[0.3, -0.6], 0.0);
+        assert_eq!(network.into_inference().predict(&[0.3, -0.6]), expected);
+    }
+
+    #[test]
+    fn test_add_skip_connection_validates_layers() {
+        let mut network = NeuroForge::with_seed(&[2, 3, 2], &[false, false, false], &[false, false, false], 51);
+        assert_eq!(network.add_skip_connection(1, 1), Err(BuildError::InvalidSkip { from: 1, to: 1, layers: 3 }));
+        assert_eq!(network.add_skip_connection(0, 3), Err(BuildError::InvalidSkip { from: 0, to: 3, layers: 3 }));
+        assert_eq!(network.add_skip_connection(0, 2), Err(BuildError::IncompatibleSkip { from: 0, to: 2, expected: 3, got: 2 }));
+        assert!(network.skip_connections().is_empty());
+    }
+
+    #[test]
+    fn test_forward_sequence_uses_each_timestamp() {
+        let mut network = NeuroForge::with_seed(&[2, 3], &[false, false], &[true, true], 52);
+        let mut reference = NeuroForge::with_seed(&[2, 3], &[false, false], &[true, true], 52);
+        let samples = vec![(vec![0.2, 0.4], 0.0), (vec![0.6, 0.1], 0.5), (vec![0.3, 0.9], 2.0)];
+
+        let outputs = network.forward_sequence(&samples);
+        let expected: Vec<Vec<f64>> = samples.iter().map(|(input, time)| reference.forward(input, *time)).collect();
+        assert_eq!(outputs, expected);
+        assert_ne!(outputs[1], network.forward(&samples[1].0, 0.0));
     }
-}
 
-impl TemporalLayer {
-    fn new(size: usize) -> Self {
-        TemporalLayer {
-            neurons: (0..size).map(|_| TemporalNeuron::new(size)).collect(),
+    #[test]
+    fn test_reset_state_isolates_sequences() {
+        // No quantum layers, so neither sequence draws from the random stream.
+        let build = || NeuroForge::with_seed(&[2, 3], &[true, false], &[false, true], 53);
+        let mut network = build();
+        for input in [vec![0.1, 0.2], vec![0.8, 0.3], vec![0.5, 0.5]] {
+            network.forward(&input, 0.0);
         }
+        network.reset_state();
+        let mut fresh = build();
+        for input in [vec![0.9, 0.1], vec![0.4, 0.7]] {
+            assert_eq!(network.forward(&input, 1.0), fresh.forward(&input, 1.0));
+        }
+        network.adapt_architecture();
+        fresh.adapt_architecture();
+        assert_eq!(network.weight_stats(), fresh.weight_stats());
+        assert_eq!(network.architecture_summary(), fresh.architecture_summary());
+
+        let mut quantum = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, false], 53);
+        quantum.forward(&[0.3, 0.6], 0.0);
+        quantum.reset_state();
+        assert_eq!(quantum.quantum_phases(), vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
     }
 
-    fn forward(&mut self, input: &[f64], time: f64) -> Vec<f64> {
-        self.neurons
-            .iter_mut()
-            .map(|neuron| neuron.activate(input, time))
-            .collect()
+    #[test]
+    fn test_heads_branch_from_the_shared_trunk() {
+        let build = || NeuroForge::with_seed(&[2, 3, 2], &[false, false, false], &[true, true, true], 54);
+        let mut network = build();
+        let head = NeuroForgeBuilder::new().temporal_layer(3).temporal_layer(1).seed(55);
+        assert_eq!(network.add_head(1, head, Loss::BinaryCrossEntropy), Ok(1));
+        assert_eq!(network.head_count(), 1);
+
+        let outputs = network.forward_multihead(&[0.2, 0.7], 0.0);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0], build().forward(&[0.2, 0.7], 0.0));
+        assert_eq!(outputs[1].len(), 1);
+
+        let inputs = vec![vec![0.1, 0.9], vec![0.8, 0.2]];
+        let targets = vec![vec![vec![0.9, 0.1], vec![1.0]], vec![vec![0.1, 0.9], vec![0.0]]];
+        let stats = network.train_multihead(&inputs, &targets, 50, 0.5);
+        assert!(stats.last().unwrap().mean_error < stats[0].mean_error);
+    }
+
+    #[test]
+    fn test_train_multihead_uses_sgd_after_another_optimizer() {
+        let inputs = vec![vec![0.1, 0.9], vec![0.8, 0.2]];
+        let targets = vec![vec![vec![0.9, 0.1], vec![1.0]], vec![vec![0.1, 0.9], vec![0.0]]];
+        let build = || {
+            let mut network = NeuroForge::with_seed(&[2, 3, 2], &[false, false, false], &[true, true, true], 57);
+            network.add_head(1, NeuroForgeBuilder::new().temporal_layer(3).temporal_layer(1).seed(58), Loss::Mse).unwrap();
+            network
+        };
+        let mut network = build();
+        // Zero epochs install Adam without changing any weight.
+        let main_targets: Vec<Vec<f64>> = targets.iter().map(|sample| sample[0].clone()).collect();
+        network.train_with_optimizer(&inputs, &main_targets, 0, &optimizer::Adam::new(0.05));
+
+        let stats = network.train_multihead(&inputs, &targets, 3, 0.5);
+        assert_eq!(stats, build().train_multihead(&inputs, &targets, 3, 0.5));
+    }
+
+    #[test]
+    fn test_add_head_validates_the_branch() {
+        let mut network = NeuroForge::with_seed(&[2, 3], &[false, false], &[true, true], 56);
+        let head = || NeuroForgeBuilder::new().temporal_layer(2);
+        assert_eq!(network.add_head(2, head(), Loss::Mse), Err(BuildError::InvalidBranch { branch: 2, layers: 2 }));
+        assert_eq!(network.add_head(0, head(), Loss::Mse), Ok(1));
+        assert_eq!(network.add_head(1, head(), Loss::Mse), Err(BuildError::IncompatibleHead { branch: 1, expected: 2, got: 3 }));
+        assert_eq!(network.add_head(1, NeuroForgeBuilder::new(), Loss::Mse), Ok(2));
+        assert_eq!(network.forward_multihead(&[0.5, 0.5], 0.0)[2].len(), 3);
     }
 
-    fn backward(&mut self, error: &[f64], learning_rate: f64) -> Vec<f64> {
-        let mut next_error = vec![0.0; self.neurons[0].input_size()];
+    #[test]
+    fn test_to_dot_lists_layers_and_edges() {
+        let network = NeuroForge::with_seed(&[2, 4, 3], &[true, false, false], &[false, true, false], 45);
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph neuroforge {"));
+        assert!(dot.contains("input [label=\"input\\n2 values\"]"));
+        assert!(dot.contains("label=\"layer 0: adaptive\""));
+        assert!(dot.contains("label=\"layer 2: quantum\""));
+        assert!(dot.contains("layer_1 [label=\"4 neurons\"]"));
+        assert!(dot.contains("input -> layer_0;"));
+        assert!(dot.contains("layer_1 -> layer_2;"));
+        assert_eq!(dot.matches("->").count(), 3);
+    }
 
-        for (neuron, &neuron_error) in self.neurons.iter_mut().zip(error.iter()) {
-            let neuron_gradients = neuron.calculate_gradients(neuron_error);
-            neuron.update_weights(&neuron_gradients, learning_rate);
+    #[test]
+    fn test_architecture_summary_follows_adaptation() {
+        let mut network = NeuroForge::with_seed(&[2, 4, 4], &[false, false, true], &[false, true, false], 44);
+        let kinds: Vec<&str> = network.architecture_summary().iter().map(|info| info.kind).collect();
+        assert_eq!(kinds, vec!["quantum", "temporal", "adaptive"]);
 
-            for (i, &gradient) in neuron_gradients.iter().enumerate() {
-                next_error[i] += gradient;
+        network.emotional_state = 1.0;
+        network.forward(&[0.1, 0.2], 0.0);
+        network.adapt_architecture();
+        let summary = network.architecture_summary();
+        assert_eq!(summary[2], LayerInfo { kind: "adaptive", input_size: 4, neuron_count: 5 });
+        match &network.layers[2] {
+            Layer::Adaptive(layer) => {
+                assert_eq!(layer.neuron_count(), 5);
+                assert_eq!(layer.importance_scores().len(), 5);
             }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_train_step_matches_one_epoch_of_train() {
+        let inputs = vec![vec![0.2, 0.7]];
+        let targets = vec![vec![1.0, 0.0]];
+        let mut stepped = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 45);
+        let mut trained = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 45);
+
+        stepped.forward(&inputs[0], 0.0);
+        let error = stepped.train_step(&targets[0], 0.3);
+        let stats = trained.train(&inputs, &targets, 1, 0.3);
+        assert_eq!(error, stats[0].mean_error);
+        assert_eq!(stepped.emotional_state(), trained.emotional_state());
+        assert_eq!(stepped.evaluate(&inputs[0], 0.0), trained.evaluate(&inputs[0], 0.0));
+    }
+
+    #[test]
+    fn test_train_step_uses_sgd_after_another_optimizer() {
+        let inputs = vec![vec![0.2, 0.7]];
+        let targets = vec![vec![1.0, 0.0]];
+        let build = || {
+            let mut network = NeuroForge::with_seed(&[2, 2], &[false, true], &[false, false], 46);
+            network.train_with_optimizer(&inputs, &targets, 3, &optimizer::Adam::new(0.05));
+            network
+        };
+        let (mut stepped, mut trained) = (build(), build());
+
+        stepped.forward(&inputs[0], 0.0);
+        let error = stepped.train_step(&targets[0], 0.3);
+        let stats = trained.train(&inputs, &targets, 1, 0.3);
+        assert_eq!(error, stats[0].mean_error);
+        assert_eq!(stepped.evaluate(&inputs[0], 0.0), trained.evaluate(&inputs[0], 0.0));
+    }
+
+    #[test]
+    fn test_frozen_forward_leaves_nothing_for_train_step() {
+        let mut network = NeuroForge::with_seed(&[2, 2, 1], &[false, true, false], &[false, false, false], 66);
+        network.forward(&[0.4, 0.6], 0.0);
+        network.freeze();
+        assert!(network.last_output.is_none());
+        network.forward(&[0.4, 0.6], 0.0);
+        network.unfreeze();
+        assert!(network.last_output.is_none());
+
+        network.forward(&[0.4, 0.6], 0.0);
+        assert!(network.train_step(&[1.0], 0.1).is_finite());
+    }
+
+    #[test]
+    fn test_training_on_no_examples_reports_zero_error() {
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 33);
+        let stats = network.train(&[], &[], 1, 0.1);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].mean_error, 0.0);
+    }
+
+    #[test]
+    fn test_train_returns_epoch_stats() {
+        let inputs = vec![vec![0.5, 0.5], vec![1.0, 0.2]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let mut network = NeuroForge::with_seed(&[2, 2], &[false, false], &[false, true], 19);
+
+        let stats = network.train(&inputs, &targets, 4, 0.1);
+        assert_eq!(stats.iter().map(|s| s.epoch).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert!(stats.iter().all(|s| s.mean_error.is_finite() && s.neuron_count == 4));
+        assert_eq!(stats[3].emotional_state, network.emotional_state());
+    }
+
+    #[test]
+    fn test_layers_keep_declaration_order() {
+        let network = NeuroForge::new(&[2, 2, 2], &[false, false, false], &[false, true, false]);
+        assert!(matches!(network.layers[0], Layer::Quantum(_)));
+        assert!(matches!(network.layers[1], Layer::Temporal(_)));
+        assert!(matches!(network.layers[2], Layer::Quantum(_)));
+    }
+
+    #[test]
+    fn test_entangled_neurons_flip_with_their_partner() {
+        let mut rng = ChaCha12Rng::seed_from_u64(41);
+        let mut plain = QuantumLayer::new(2, 2, QuantumMode::Deterministic, false, false, WeightInit::default(), &mut rng);
+        plain.weights = Array2::eye(2);
+        let mut entangled = plain.clone();
+        entangled.entangle_pairs(&[(0, 1)]);
+        assert!(entangled.neurons.iter().all(QuantumNeuron::is_entangled));
+
+        // Both neurons collapse: neuron 0 to |1>, neuron 1 (no rotation) to |0>.
+        let input = [0.2, 0.0];
+        for layer in [&mut plain, &mut entangled] {
+            assert_eq!(layer.forward(&input, 0.6, &mut rng), vec![1.0, 0.0]);
         }
 
-        next_error
+        // On the next pass each neuron rotates on from its own collapsed state,
+        // unless it is entangled, in which case it starts from its partner's outcome.
+        let angle = 0.4 * std::f64::consts::PI;
+        let output = plain.forward(&input, 0.0, &mut rng);
+        assert!((output[0] - angle.cos()).abs() < 1e-12);
+        assert_eq!(output[1], 0.0);
+        let output = entangled.forward(&input, 0.0, &mut rng);
+        assert!((output[0] - angle.sin()).abs() < 1e-12);
+        assert_eq!(output[1], 1.0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_learned_quantum_mix_moves_during_training() {
+        let inputs = vec![vec![0.2, 0.7], vec![0.9, 0.1]];
+        let targets = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let mixes = |learn: bool| {
+            let mut network = NeuroForgeBuilder::new().quantum_layer(2).quantum_layer(2).seed(59).build().unwrap();
+            network.set_quantum_mix(1, 0.5, learn);
+            network.train(&inputs, &targets, 3, 0.1);
+            quantum_layer(&network, 1).neurons.iter().map(QuantumNeuron::mix).collect::<Vec<f64>>()
+        };
+        assert_eq!(mixes(false), vec![0.5, 0.5]);
+        assert!(mixes(true).iter().all(|&mix| mix != 0.5 && (0.0..=1.0).contains(&mix)));
+    }
 
     #[test]
-    fn test_neuroforge_creation() {
-        let network = NeuroForge::new(&[2, 3, 1], &[false, false, false], &[false, false, false]);
-        assert_eq!(network.quantum_layers.len(), 3);
-        assert!(network.adaptive_layers.is_empty());
-        assert!(network.temporal_layers.is_empty());
+    fn test_dead_neurons_reports_near_zero_weight_rows() {
+        let mut network = NeuroForge::with_seed(&[2, 3, 3], &[false, false, true], &[false, true, false], 61);
+        assert!(network.dead_neurons(1e-6).is_empty());
+        // Neuron 2 of the temporal layer, fed from two inputs.
+        *network.layers[1].weight_mut(4) = 1e-8;
+        *network.layers[1].weight_mut(5) = -1e-8;
+        // Neuron 1 of the adaptive layer, fed from three inputs.
+        *network.layers[2].weight_mut(3) = 0.0;
+        *network.layers[2].weight_mut(4) = 0.0;
+        *network.layers[2].weight_mut(5) = 1e-9;
+        *network.layers[0].weight_mut(0) = 0.0;
+        assert_eq!(network.dead_neurons(1e-6), vec![(1, 2), (2, 1)]);
+        assert_eq!(network.dead_neurons(f64::INFINITY).len(), 8);
+    }
+
+    #[test]
+    fn test_prune_weights_zeroes_small_weights_and_reports_sparsity() {
+        let mut network = NeuroForge::with_seed(&[3, 4, 2], &[false, false, false], &[false, true, false], 62);
+        network.freeze_layer(2);
+        let frozen_before = network.layers[2].weight_values();
+        let small = network.layers[..2].iter()
+            .flat_map(Layer::weight_values)
+            .filter(|&w| w != 0.0 && w.abs() < 0.2)
+            .count();
+        assert!(small > 0);
+
+        assert_eq!(network.prune_weights(0.2), small);
+        assert_eq!(network.prune_weights(0.2), 0);
+        let total: usize = network.layers.iter().map(Layer::weight_count).sum();
+        assert!((network.sparsity() - small as f64 / total as f64).abs() < 1e-12);
+        assert!(network.layers[..2].iter().flat_map(Layer::weight_values).all(|w| w == 0.0 || w.abs() >= 0.2));
+        assert_eq!(network.layers[2].weight_values(), frozen_before);
+    }
+
+    #[test]
+    fn test_prune_neurons_shrinks_the_next_layers_inputs() {
+        let build = || NeuroForgeBuilder::new()
+            .quantum_layer(3)
+            .adaptive_layer(3, 3, 1, 0.5)
+            .temporal_layer(4)
+            .quantum_layer(2)
+            .seed(63)
+            .build()
+            .unwrap();
+        let input = [0.2, 0.5, 0.8];
+
+        // A skip into the layer after the adaptive one pins its width.
+        let mut skipped = build();
+        skipped.add_skip_connection(0, 2).unwrap();
+        skipped.forward(&input, 0.0);
+        assert!(skipped.prune_neurons(f64::INFINITY).is_empty());
+
+        let mut network = build();
+        network.forward(&input, 0.0);
+        assert!(network.prune_neurons(f64::NEG_INFINITY).is_empty());
+        // Every neuron is below an infinite threshold, so only the minimum of one stays.
+        let removed = network.prune_neurons(f64::INFINITY);
+        assert_eq!(removed.len(), 2);
+        assert!(removed.iter().all(|&(layer, _)| layer == 1));
+        let summary = network.architecture_summary();
+        assert_eq!((summary[1].neuron_count, summary[2].input_size), (1, 1));
+
+        assert_eq!(network.forward(&input, 1.0).len(), 2);
+        let stats = network.train(&[input.to_vec()], &[vec![0.3, 0.7]], 2, 0.1);
+        assert!(stats.iter().all(|epoch| epoch.mean_error.is_finite()));
+    }
+
+    #[test]
+    fn test_tanh_adaptive_layer_learns_negative_targets() {
+        let mut network = NeuroForgeBuilder::new()
+            .adaptive_layer(2, 2, 2, 0.5)
+            .adaptive_activation(activation::Activation::Tanh)
+            .seed(4)
+            .build()
+            .unwrap();
+        let inputs = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        let targets = vec![vec![-0.5, 0.5], vec![0.5, -0.5]];
+        let stats = network.train_with_optimizer(&inputs, &targets, 500, &optimizer::Adam::new(0.05));
+        assert!(stats.last().unwrap().mean_error < stats[0].mean_error);
+        // A sigmoid layer could not output below 0.
+        for (input, expected) in inputs.iter().zip(targets.iter()) {
+            let output = network.forward(input, 0.0);
+            assert!(output.iter().zip(expected.iter()).all(|(&y, &t)| (y - t).abs() < 0.1));
+        }
+    }
+
+    #[test]
+    fn test_refractory_network_still_learns_xor() {
+        let mut network = NeuroForgeBuilder::new()
+            .temporal_layer(2)
+            .temporal_layer(3)
+            .temporal_layer(1)
+            .refractory(1.0, 0.5)
+            .seed(0)
+            .build()
+            .unwrap();
+        let inputs = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+        network.train_with_optimizer(&inputs, &targets, 1000, &optimizer::Adam::new(0.05));
+
+        // A firing during training must neither silence the next example nor
+        // carry over into the inference model.
+        let output = network.forward(&inputs[1], 0.0);
+        assert!((output[0] - 1.0).abs() < 0.1);
+        let model = network.into_inference();
+        for (input, expected) in inputs.iter().zip(targets.iter()) {
+            assert!((model.predict(input)[0] - expected[0]).abs() < 0.1);
+        }
     }
 
     #[test]
     fn test_forward_pass() {
-        let mut network = NeuroForge::new(&[2, 3, 1], &[false, false, false], &[false, false, false]);
+        let mut network = NeuroForge::with_unit_quantum_output(&[2, 3, 1], &[false, false, false], &[false, false, false], true);
         let input = vec![1.0, 0.0];
         let output = network.forward(&input, 0.0);
         assert_eq!(output.len(), 1);
+        // Without unit output, a superposed quantum neuron can output down to -1.
         assert!(output[0] >= 0.0 && output[0] <= 1.0);
     }
 
     #[test]
     fn test_training() {
-        let mut network = NeuroForge::new(&[2, 3, 1], &[false, false, false], &[false, false, false]);
+        // A seeded 2 -> 3 -> 1 funnel of temporal layers, whose sigmoid neurons
+        // learn XOR; the superposed output of quantum layers does not.
+        let mut network = NeuroForgeBuilder::new().temporal_layer(2).temporal_layer(3).temporal_layer(1).seed(0).build().unwrap();
         let inputs = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
         let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
-        network.train(&inputs, &targets, 1000, 0.1);
+        network.train_with_optimizer(&inputs, &targets, 1000, &optimizer::Adam::new(0.05));
         // Check if the network has learned XOR function (approximately)
         for (input, expected) in inputs.iter().zip(targets.iter()) {
             let output = network.forward(input, 0.0);
             assert!((output[0] - expected[0]).abs() < 0.1);
         }
     }
+
+    #[test]
+    fn test_quantum_training_reduces_the_error() {
+        // The original 2 -> 3 -> 1 quantum funnel, seeded so the run repeats:
+        // it cannot fit XOR closely, but its error still ends below where it
+        // started, with or without the rayon feature.
+        let mut network = NeuroForge::with_seed(&[2, 3, 1], &[false, false, false], &[false, false, false], 15);
+        let inputs = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+        let stats = network.train(&inputs, &targets, 200, 0.1);
+        assert!(stats.iter().all(|epoch| epoch.mean_error.is_finite()));
+        assert!(stats.last().unwrap().mean_error < stats[0].mean_error);
+    }
 }
\ No newline at end of file
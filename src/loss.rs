@@ -0,0 +1,129 @@
+/// Loss function comparing network output to a target.
+///
+/// Both the value and the gradient are averaged over the compared elements.
+/// Outputs beyond the target's length (e.g. appended symbolic rule outputs
+/// without a target) are ignored and receive zero gradient.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Loss {
+    /// Mean squared error.
+    #[default]
+    Mse,
+    /// Binary cross-entropy. Outputs are clamped into `[1e-7, 1 - 1e-7]` to avoid `ln(0)`.
+    BinaryCrossEntropy,
+    /// Categorical cross-entropy `-t ln(o)`, meant for outputs that form a
+    /// probability distribution. Outputs are clamped to at least `1e-7`.
+    CrossEntropy,
+    /// Huber loss: `e^2 / 2` for an error `e` up to `delta` in magnitude and
+    /// `delta * (|e| - delta / 2)` beyond, so outliers pull on the weights
+    /// linearly rather than quadratically. `delta` must be positive; training
+    /// and [`Loss::gradient`] panic otherwise.
+    Huber { delta: f64 },
+}
+
+const BCE_EPSILON: f64 = 1e-7;
+
+impl Loss {
+    /// Panics unless the loss's parameters are usable, i.e. a Huber `delta`
+    /// is positive and not NaN.
+    pub(crate) fn assert_valid(&self) {
+        if let Loss::Huber { delta } = *self {
+            assert!(delta > 0.0, "Huber delta must be positive, got {}", delta);
+        }
+    }
+
+    pub fn value(&self, output: &[f64], target: &[f64]) -> f64 {
+        let n = output.len().min(target.len());
+        if n == 0 {
+            return 0.0;
+        }
+
+        let total: f64 = output.iter()
+            .zip(target.iter())
+            .map(|(&o, &t)| match self {
+                Loss::Mse => (o - t).powi(2),
+                Loss::BinaryCrossEntropy => {
+                    let o = o.clamp(BCE_EPSILON, 1.0 - BCE_EPSILON);
+                    -(t * o.ln() + (1.0 - t) * (1.0 - o).ln())
+                }
+                Loss::CrossEntropy => -t * o.max(BCE_EPSILON).ln(),
+                Loss::Huber { delta } => {
+                    let error = (o - t).abs();
+                    if error <= *delta {
+                        0.5 * error * error
+                    } else {
+                        delta * (error - 0.5 * delta)
+                    }
+                }
+            })
+            .sum();
+        total / n as f64
+    }
+
+    /// Gradient of [`Loss::value`] with respect to each output element. The
+    /// returned vector has the same length as `output`.
+    pub fn gradient(&self, output: &[f64], target: &[f64]) -> Vec<f64> {
+        self.assert_valid();
+        let n = output.len().min(target.len());
+        let mut gradient = vec![0.0; output.len()];
+        for (g, (&o, &t)) in gradient.iter_mut().zip(output.iter().zip(target.iter())) {
+            *g = match self {
+                Loss::Mse => 2.0 * (o - t),
+                Loss::BinaryCrossEntropy => {
+                    let o = o.clamp(BCE_EPSILON, 1.0 - BCE_EPSILON);
+                    (o - t) / (o * (1.0 - o))
+                }
+                Loss::CrossEntropy => -t / o.max(BCE_EPSILON),
+                Loss::Huber { delta } => (o - t).clamp(-*delta, *delta),
+            } / n as f64;
+        }
+        gradient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_matches_finite_differences() {
+        let target = vec![1.0, 0.0, 0.3];
+        let output = vec![0.7, 0.2, 0.5];
+        let epsilon = 1e-6;
+        for loss in [Loss::Mse, Loss::BinaryCrossEntropy, Loss::CrossEntropy, Loss::Huber { delta: 0.25 }] {
+            let gradient = loss.gradient(&output, &target);
+            for i in 0..output.len() {
+                let mut plus = output.clone();
+                let mut minus = output.clone();
+                plus[i] += epsilon;
+                minus[i] -= epsilon;
+                let numeric = (loss.value(&plus, &target) - loss.value(&minus, &target)) / (2.0 * epsilon);
+                assert!((numeric - gradient[i]).abs() < 1e-5, "{:?} index {}", loss, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_huber_is_quadratic_within_delta_and_linear_beyond() {
+        let loss = Loss::Huber { delta: 1.0 };
+        assert_eq!(loss.value(&[0.5], &[0.0]), 0.125);
+        assert_eq!(loss.value(&[10.0], &[0.0]), 9.5);
+        assert_eq!(loss.gradient(&[0.5, 10.0, -10.0], &[0.0, 0.0, 0.0]), vec![0.5 / 3.0, 1.0 / 3.0, -1.0 / 3.0]);
+    }
+
+    #[test]
+    fn test_huber_delta_must_be_positive() {
+        Loss::Huber { delta: 0.5 }.assert_valid();
+        for delta in [0.0, -1.0, f64::NAN] {
+            let result = std::panic::catch_unwind(|| Loss::Huber { delta }.gradient(&[1.0], &[0.0]));
+            assert!(result.is_err(), "delta {}", delta);
+        }
+    }
+
+    #[test]
+    fn test_bce_is_finite_at_saturated_outputs() {
+        let loss = Loss::BinaryCrossEntropy;
+        assert!(loss.value(&[0.0, 1.0], &[1.0, 0.0]).is_finite());
+        assert!(loss.gradient(&[0.0, 1.0], &[1.0, 0.0]).iter().all(|g| g.is_finite()));
+    }
+}
@@ -0,0 +1,40 @@
+use ndarray::Array2;
+
+/// Fraction of `predictions` equal to the matching entry of `targets`.
+/// Returns 0.0 for empty input.
+pub fn accuracy(predictions: &[usize], targets: &[usize]) -> f64 {
+    let n = predictions.len().min(targets.len());
+    if n == 0 {
+        return 0.0;
+    }
+    let correct = predictions.iter().zip(targets.iter()).filter(|(p, t)| p == t).count();
+    correct as f64 / n as f64
+}
+
+/// Counts of every (target, prediction) pair: entry `[[t, p]]` is the number
+/// of samples of class `t` predicted as class `p`. Panics if a class index is
+/// not below `num_classes`.
+pub fn confusion_matrix(predictions: &[usize], targets: &[usize], num_classes: usize) -> Array2<usize> {
+    let mut matrix = Array2::zeros((num_classes, num_classes));
+    for (&prediction, &target) in predictions.iter().zip(targets.iter()) {
+        matrix[[target, prediction]] += 1;
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accuracy_and_confusion_matrix() {
+        let predictions = [0, 1, 1, 2, 0];
+        let targets = [0, 1, 2, 2, 1];
+        assert_eq!(accuracy(&predictions, &targets), 0.6);
+        assert_eq!(accuracy(&[], &[]), 0.0);
+
+        let matrix = confusion_matrix(&predictions, &targets, 3);
+        assert_eq!(matrix, ndarray::arr2(&[[1, 0, 0], [1, 1, 0], [0, 1, 1]]));
+        assert_eq!(matrix.sum(), predictions.len());
+    }
+}
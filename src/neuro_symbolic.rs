@@ -1,75 +1,446 @@
-use std::collections::HashMap;
+pub type RuleFn = Box<dyn Fn(&[f64]) -> f64>;
+pub type VecRuleFn = Box<dyn Fn(&[f64]) -> Vec<f64>>;
+pub type GateFn = Box<dyn Fn(&[f64]) -> bool>;
 
+/// Built-in rules with exact gradients, registered with
+/// [`NeuroSymbolicLayer::add_builtin_rule`]. Each produces one value from the
+/// whole neural output, and an empty output gives `Sum`, `Mean` and `Max` 0.0
+/// and `Product` 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolicRule {
+    Sum,
+    Mean,
+    Max,
+    Product,
+}
+
+impl SymbolicRule {
+    pub fn evaluate(&self, input: &[f64]) -> f64 {
+        match self {
+            SymbolicRule::Sum => input.iter().sum(),
+            SymbolicRule::Mean if input.is_empty() => 0.0,
+            SymbolicRule::Mean => input.iter().sum::<f64>() / input.len() as f64,
+            SymbolicRule::Max => input.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            SymbolicRule::Product => input.iter().product(),
+        }
+    }
+
+    /// Derivative of [`SymbolicRule::evaluate`] with respect to each input.
+    /// `Max` passes the whole gradient to its first largest input.
+    pub fn gradient(&self, input: &[f64]) -> Vec<f64> {
+        match self {
+            SymbolicRule::Sum => vec![1.0; input.len()],
+            SymbolicRule::Mean => vec![1.0 / input.len() as f64; input.len()],
+            SymbolicRule::Max => {
+                let mut gradient = vec![0.0; input.len()];
+                let largest = input.iter().enumerate().fold(None, |best: Option<(usize, f64)>, (i, &x)| match best {
+                    Some((_, value)) if value >= x => best,
+                    _ => Some((i, x)),
+                });
+                if let Some((i, _)) = largest {
+                    gradient[i] = 1.0;
+                }
+                gradient
+            }
+            SymbolicRule::Product => {
+                // Product of all other inputs, from prefix and suffix products
+                // so that zeros need no special case.
+                let mut gradient = vec![1.0; input.len()];
+                let mut prefix = 1.0;
+                for (g, &x) in gradient.iter_mut().zip(input) {
+                    *g = prefix;
+                    prefix *= x;
+                }
+                let mut suffix = 1.0;
+                for (g, &x) in gradient.iter_mut().zip(input).rev() {
+                    *g *= suffix;
+                    suffix *= x;
+                }
+                gradient
+            }
+        }
+    }
+}
+
+/// Default absolute step of the central differences in
+/// [`NeuroSymbolicLayer::backward`].
+pub const DEFAULT_EPSILON: f64 = 1e-5;
+
+/// Applies named symbolic rules to the neural output.
+///
+/// When serialized (feature `serde`) only the rule names and output lengths are
+/// written, since the rules themselves are closures. A deserialized layer holds
+/// no-op rules that output zeros; call [`NeuroSymbolicLayer::add_rule`] or
+/// [`NeuroSymbolicLayer::add_rule_vec`] with the same name to restore a rule's
+/// behaviour.
+///
+/// Rules are kept in insertion order, which is also the order their outputs are
+/// appended by [`NeuroSymbolicLayer::process`].
+///
+/// Every rule's output is scaled by a trainable weight, initially 1.0, which
+/// [`NeuroSymbolicLayer::backward`] accumulates gradients for.
+///
+/// `backward` takes closure rules' gradients by central differences, which
+/// costs two rule evaluations per input; [`SymbolicRule`]s use their exact
+/// gradient instead, and are restored in full when deserialized. The step for
+/// input `x` is `max(epsilon, relative_epsilon * |x|)`; see
+/// [`NeuroSymbolicLayer::set_epsilon`].
 pub struct NeuroSymbolicLayer {
-    symbolic_rules: HashMap<String, Box<dyn Fn(&[f64]) -> f64>>,
+    symbolic_rules: Vec<RegisteredRule>,
+    neural_output: Vec<f64>,
+    // Perturbed copy of `neural_output`, kept between `backward` calls so the
+    // finite differences do not allocate one per input.
+    probe: Vec<f64>,
+    epsilon: f64,
+    relative_epsilon: f64,
+}
+
+struct RegisteredRule {
+    name: String,
+    rule: VecRuleFn,
+    // Number of values the rule appended on the last `process`, which places
+    // the following rules' outputs in `backward`.
+    arity: usize,
+    gate: Option<GateFn>,
+    // Set for built-in rules, whose exact gradient replaces finite differences.
+    builtin: Option<SymbolicRule>,
+    // Whether the gate let the rule fire on the last `process`.
+    gate_open: bool,
+    weight: f64,
+    // Unweighted output of the last `process`.
+    raw_output: Vec<f64>,
+    weight_gradient_sum: f64,
+    accumulated_steps: usize,
+}
+
+impl RegisteredRule {
+    fn new(name: &str, rule: VecRuleFn, arity: usize, gate: Option<GateFn>) -> Self {
+        RegisteredRule {
+            name: name.to_string(),
+            rule,
+            arity,
+            gate,
+            builtin: None,
+            gate_open: true,
+            weight: 1.0,
+            raw_output: Vec::new(),
+            weight_gradient_sum: 0.0,
+            accumulated_steps: 0,
+        }
+    }
+
+    fn builtin(name: &str, builtin: SymbolicRule) -> Self {
+        RegisteredRule {
+            builtin: Some(builtin),
+            ..RegisteredRule::new(name, Box::new(move |input: &[f64]| vec![builtin.evaluate(input)]), 1, None)
+        }
+    }
+
+    fn is_open(&self, input: &[f64]) -> bool {
+        self.gate.as_ref().is_none_or(|gate| gate(input))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedSymbolicLayer {
+    rule_names: Vec<String>,
+    rule_arities: Vec<usize>,
+    #[serde(default)]
+    rule_weights: Vec<f64>,
+    #[serde(default)]
+    rule_builtins: Vec<Option<SymbolicRule>>,
     neural_output: Vec<f64>,
+    #[serde(default = "default_epsilon")]
+    epsilon: f64,
+    #[serde(default)]
+    relative_epsilon: f64,
+}
+
+#[cfg(feature = "serde")]
+fn default_epsilon() -> f64 {
+    DEFAULT_EPSILON
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NeuroSymbolicLayer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedSymbolicLayer {
+            rule_names: self.symbolic_rules.iter().map(|rule| rule.name.clone()).collect(),
+            rule_arities: self.symbolic_rules.iter().map(|rule| rule.arity).collect(),
+            rule_weights: self.symbolic_rules.iter().map(|rule| rule.weight).collect(),
+            rule_builtins: self.symbolic_rules.iter().map(|rule| rule.builtin).collect(),
+            neural_output: self.neural_output.clone(),
+            epsilon: self.epsilon,
+            relative_epsilon: self.relative_epsilon,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NeuroSymbolicLayer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedSymbolicLayer::deserialize(deserializer)?;
+        // Layers saved before rule weights existed load with every weight at 1.0.
+        let weights = serialized.rule_weights.into_iter().chain(std::iter::repeat(1.0));
+        let builtins = serialized.rule_builtins.into_iter().chain(std::iter::repeat(None));
+        Ok(NeuroSymbolicLayer {
+            symbolic_rules: serialized
+                .rule_names
+                .into_iter()
+                .zip(serialized.rule_arities)
+                .zip(weights.zip(builtins))
+                .map(|((name, arity), (weight, builtin))| {
+                    let rule = match builtin {
+                        Some(builtin) => RegisteredRule::builtin(&name, builtin),
+                        None => RegisteredRule::new(&name, Box::new(move |_: &[f64]| vec![0.0; arity]), arity, None),
+                    };
+                    RegisteredRule { weight, ..rule }
+                })
+                .collect(),
+            neural_output: serialized.neural_output,
+            probe: Vec::new(),
+            epsilon: serialized.epsilon,
+            relative_epsilon: serialized.relative_epsilon,
+        })
+    }
+}
+
+impl Default for NeuroSymbolicLayer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NeuroSymbolicLayer {
     pub fn new() -> Self {
         NeuroSymbolicLayer {
-            symbolic_rules: HashMap::new(),
+            symbolic_rules: Vec::new(),
             neural_output: Vec::new(),
+            probe: Vec::new(),
+            epsilon: DEFAULT_EPSILON,
+            relative_epsilon: 0.0,
+        }
+    }
+
+    /// Sets the finite-difference step used for closure rules: `epsilon` is
+    /// the smallest step, and `relative_epsilon` scales it with the magnitude
+    /// of each input so rules on large values stay accurate. The defaults are
+    /// [`DEFAULT_EPSILON`] and 0.0, a purely absolute step.
+    pub fn set_epsilon(&mut self, epsilon: f64, relative_epsilon: f64) {
+        assert!(epsilon > 0.0, "epsilon must be positive");
+        assert!(relative_epsilon >= 0.0, "relative_epsilon must not be negative");
+        self.epsilon = epsilon;
+        self.relative_epsilon = relative_epsilon;
+    }
+
+    pub fn epsilon(&self) -> (f64, f64) {
+        (self.epsilon, self.relative_epsilon)
+    }
+
+    /// Adds a rule, or replaces the rule of the same name in its existing position.
+    pub fn add_rule(&mut self, name: &str, rule: RuleFn) {
+        self.insert_rule(name, Box::new(move |input: &[f64]| vec![rule(input)]), 1, None);
+    }
+
+    /// Adds a built-in rule, or replaces the rule of the same name in its
+    /// existing position. Unlike closure rules, its gradient is exact.
+    pub fn add_builtin_rule(&mut self, name: &str, rule: SymbolicRule) {
+        let registered = RegisteredRule::builtin(name, rule);
+        match self.symbolic_rules.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => {
+                existing.rule = registered.rule;
+                existing.arity = registered.arity;
+                existing.gate = None;
+                existing.builtin = registered.builtin;
+            }
+            None => self.symbolic_rules.push(registered),
+        }
+    }
+
+    /// Adds a rule producing several values, all of which are appended by
+    /// [`NeuroSymbolicLayer::process`]. The rule must always return the same
+    /// number of values.
+    pub fn add_rule_vec(&mut self, name: &str, rule: VecRuleFn) {
+        self.insert_rule(name, rule, 0, None);
+    }
+
+    /// Adds a rule that only fires when `gate` accepts the neural output. While
+    /// the gate is closed the rule contributes a neutral `0.0`, so the output
+    /// length does not change.
+    pub fn add_gated_rule(&mut self, name: &str, rule: RuleFn, gate: GateFn) {
+        self.insert_rule(name, Box::new(move |input: &[f64]| vec![rule(input)]), 1, Some(gate));
+    }
+
+    fn insert_rule(&mut self, name: &str, rule: VecRuleFn, arity: usize, gate: Option<GateFn>) {
+        match self.symbolic_rules.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => {
+                existing.rule = rule;
+                existing.arity = arity;
+                existing.gate = gate;
+                existing.builtin = None;
+            }
+            None => self.symbolic_rules.push(RegisteredRule::new(name, rule, arity, gate)),
         }
     }
 
-    pub fn add_rule(&mut self, name: &str, rule: Box<dyn Fn(&[f64]) -> f64>) {
-        self.symbolic_rules.insert(name.to_string(), rule);
+    /// Removes the rule called `name`, returning whether it was registered.
+    pub fn remove_rule(&mut self, name: &str) -> bool {
+        let before = self.symbolic_rules.len();
+        self.symbolic_rules.retain(|existing| existing.name != name);
+        self.symbolic_rules.len() != before
+    }
+
+    /// Names of the registered rules, in application order.
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.symbolic_rules.iter().map(|rule| rule.name.as_str()).collect()
+    }
+
+    pub fn has_rule(&self, name: &str) -> bool {
+        self.symbolic_rules.iter().any(|existing| existing.name == name)
     }
 
+    /// Current weight of the rule called `name`.
+    pub fn rule_weight(&self, name: &str) -> Option<f64> {
+        self.symbolic_rules.iter().find(|existing| existing.name == name).map(|rule| rule.weight)
+    }
+
+    /// Sets the weight of the rule called `name`, returning whether it was registered.
+    pub fn set_rule_weight(&mut self, name: &str, weight: f64) -> bool {
+        match self.symbolic_rules.iter_mut().find(|existing| existing.name == name) {
+            Some(rule) => {
+                rule.weight = weight;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Appends every rule's weighted output, each computed from the neural
+    /// `input`, to `input`.
     pub fn process(&mut self, mut input: Vec<f64>) -> Vec<f64> {
         self.neural_output = input.clone();
         
-        for rule in self.symbolic_rules.values() {
-            let symbolic_output = rule(&input);
-            input.push(symbolic_output);
+        for rule in &mut self.symbolic_rules {
+            rule.raw_output = (rule.rule)(&self.neural_output);
+            rule.arity = rule.raw_output.len();
+            rule.gate_open = rule.is_open(&self.neural_output);
+            if rule.gate_open {
+                input.extend(rule.raw_output.iter().map(|&value| rule.weight * value));
+            } else {
+                input.resize(input.len() + rule.arity, 0.0);
+            }
         }
         
         input
     }
 
-    pub fn backward(&self, error: &[f64]) -> Vec<f64> {
-        let mut neural_error = vec![0.0; self.neural_output.len()];
-        let mut symbolic_gradients = HashMap::new();
-
-        // Calculate gradients for symbolic rules
-        for (name, rule) in &self.symbolic_rules {
-            let mut gradient = vec![0.0; self.neural_output.len()];
-            let epsilon = 1e-5;
+    /// Computes the same output as [`NeuroSymbolicLayer::process`] without
+    /// caching anything for [`NeuroSymbolicLayer::backward`].
+    pub fn evaluate(&self, mut input: Vec<f64>) -> Vec<f64> {
+        let neural_output = input.clone();
+        for rule in &self.symbolic_rules {
+            let symbolic_output = (rule.rule)(&neural_output);
+            if rule.is_open(&neural_output) {
+                input.extend(symbolic_output.iter().map(|&value| rule.weight * value));
+            } else {
+                input.resize(input.len() + symbolic_output.len(), 0.0);
+            }
+        }
 
-            for i in 0..self.neural_output.len() {
-                let mut pos_input = self.neural_output.clone();
-                let mut neg_input = self.neural_output.clone();
-                pos_input[i] += epsilon;
-                neg_input[i] -= epsilon;
+        input
+    }
 
-                let pos_output = rule(&pos_input);
-                let neg_output = rule(&neg_input);
-                gradient[i] = (pos_output - neg_output) / (2.0 * epsilon);
+    /// Returns the error with respect to the neural output of the last
+    /// [`NeuroSymbolicLayer::process`], and accumulates each rule weight's
+    /// gradient until [`NeuroSymbolicLayer::apply_gradients`] is called.
+    pub fn backward(&mut self, error: &[f64]) -> Vec<f64> {
+        let mut rule_index = self.neural_output.len();
+        for rule in &mut self.symbolic_rules {
+            if rule.gate_open {
+                rule.weight_gradient_sum += rule.raw_output.iter()
+                    .zip(&error[rule_index..])
+                    .map(|(&value, &e)| value * e)
+                    .sum::<f64>();
             }
-
-            symbolic_gradients.insert(name.clone(), gradient);
+            rule.accumulated_steps += 1;
+            rule_index += rule.arity;
         }
 
-        // Combine gradients from neural and symbolic parts
-        for i in 0..self.neural_output.len() {
-            neural_error[i] += error[i]; // Direct error from neural part
+        // Direct error from the neural part, plus each rule's contribution
+        // through central differences. One probe buffer is reused for every
+        // perturbation, and each Jacobian column is folded into the error as
+        // soon as it is computed.
+        let neural_len = self.neural_output.len();
+        let mut neural_error = error[..neural_len].to_vec();
+        self.probe.clear();
+        self.probe.extend_from_slice(&self.neural_output);
 
-            // Add contributions from symbolic rules
-            for (name, gradient) in &symbolic_gradients {
-                let rule_index = self.neural_output.len() + self.symbolic_rules.keys().position(|k| k == name).unwrap();
-                neural_error[i] += error[rule_index] * gradient[i];
+        // Symbolic outputs are appended in order after the neural output.
+        let mut rule_index = neural_len;
+        for rule in &self.symbolic_rules {
+            // A closed gate appended a constant, which passes back no error.
+            if let (true, Some(builtin)) = (rule.gate_open, rule.builtin) {
+                let scale = error[rule_index] * rule.weight;
+                for (e, g) in neural_error.iter_mut().zip(builtin.gradient(&self.neural_output)) {
+                    *e += scale * g;
+                }
+            } else if rule.gate_open {
+                let rule_error = &error[rule_index..rule_index + rule.arity];
+                for (i, value) in self.neural_output.iter().enumerate() {
+                    let epsilon = self.epsilon.max(self.relative_epsilon * value.abs());
+                    self.probe[i] = value + epsilon;
+                    let pos_output = (rule.rule)(&self.probe);
+                    self.probe[i] = value - epsilon;
+                    let neg_output = (rule.rule)(&self.probe);
+                    self.probe[i] = *value;
+
+                    neural_error[i] += pos_output.iter().zip(&neg_output).zip(rule_error)
+                        .map(|((pos, neg), e)| e * rule.weight * (pos - neg) / (2.0 * epsilon))
+                        .sum::<f64>();
+                }
             }
+            rule_index += rule.arity;
         }
 
         neural_error
     }
 
+    /// Takes a gradient descent step on every rule weight with the gradients
+    /// accumulated since the last call, averaged over the accumulated steps.
+    pub fn apply_gradients(&mut self, learning_rate: f64) {
+        for rule in &mut self.symbolic_rules {
+            if rule.accumulated_steps > 0 {
+                rule.weight -= learning_rate * rule.weight_gradient_sum / rule.accumulated_steps as f64;
+            }
+            rule.weight_gradient_sum = 0.0;
+            rule.accumulated_steps = 0;
+        }
+    }
+
+    /// Discards the accumulated rule weight gradients without applying them.
+    pub(crate) fn clear_gradients(&mut self) {
+        self.apply_gradients(0.0);
+    }
+
+    /// Describes each rule's output for the last processed input. Gated rules
+    /// also report whether their gate was on or off.
     pub fn explain(&self) -> Vec<String> {
         let mut explanations = Vec::new();
-        for (name, rule) in &self.symbolic_rules {
-            let output = rule(&self.neural_output);
-            explanations.push(format!("Rule '{}' output: {:.2}", name, output));
+        for rule in &self.symbolic_rules {
+            let output = (rule.rule)(&self.neural_output);
+            let mut explanation = if let [value] = output[..] {
+                format!("Rule '{}' output: {:.2}", rule.name, value)
+            } else {
+                let values: Vec<String> = output.iter().map(|value| format!("{:.2}", value)).collect();
+                format!("Rule '{}' output: [{}]", rule.name, values.join(", "))
+            };
+            if rule.gate.is_some() {
+                explanation.push_str(if rule.gate_open { " (gate on)" } else { " (gate off)" });
+            }
+            explanations.push(explanation);
         }
         explanations
     }
@@ -105,4 +476,161 @@ mod tests {
         let explanations = layer.explain();
         assert_eq!(explanations, vec!["Rule 'sum' output: 6.00"]);
     }
+
+    #[test]
+    fn test_backward_attributes_error_to_matching_rule() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule("first", Box::new(|inputs: &[f64]| inputs[0]));
+        layer.add_rule("double_second", Box::new(|inputs: &[f64]| 2.0 * inputs[1]));
+        layer.add_rule("triple_third", Box::new(|inputs: &[f64]| 3.0 * inputs[2]));
+
+        let output = layer.process(vec![1.0, 2.0, 3.0]);
+        assert_eq!(output, vec![1.0, 2.0, 3.0, 1.0, 4.0, 9.0]);
+
+        // Each rule only depends on one input, so its error must land there alone.
+        let gradients = layer.backward(&[0.0, 0.0, 0.0, 1.0, 10.0, 100.0]);
+        let expected = [1.0, 20.0, 300.0];
+        for (gradient, expected) in gradients.iter().zip(expected.iter()) {
+            assert!((gradient - expected).abs() < 1e-6, "{:?}", gradients);
+        }
+    }
+
+    #[test]
+    fn test_vector_rule_distributes_error_across_outputs() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule_vec("pair", Box::new(|inputs: &[f64]| vec![inputs[0] * inputs[1], -inputs[1]]));
+        layer.add_rule("first", Box::new(|inputs: &[f64]| inputs[0]));
+
+        let output = layer.process(vec![2.0, 3.0]);
+        assert_eq!(output, vec![2.0, 3.0, 6.0, -3.0, 2.0]);
+        assert_eq!(layer.explain(), vec!["Rule 'pair' output: [6.00, -3.00]", "Rule 'first' output: 2.00"]);
+
+        // d(x0*x1) = (x1, x0), d(-x1) = (0, -1), d(x0) = (1, 0)
+        let gradients = layer.backward(&[0.0, 0.0, 1.0, 10.0, 100.0]);
+        let expected = [3.0 + 100.0, 2.0 - 10.0];
+        for (gradient, expected) in gradients.iter().zip(expected.iter()) {
+            assert!((gradient - expected).abs() < 1e-6, "{:?}", gradients);
+        }
+    }
+
+    #[test]
+    fn test_gated_rule_fires_only_when_confident() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_gated_rule(
+            "confident_sum",
+            Box::new(|inputs: &[f64]| inputs.iter().sum()),
+            Box::new(|inputs: &[f64]| inputs.iter().any(|&x| x > 0.9)),
+        );
+
+        assert_eq!(layer.process(vec![0.95, 0.5]), vec![0.95, 0.5, 1.45]);
+        assert_eq!(layer.explain(), vec!["Rule 'confident_sum' output: 1.45 (gate on)"]);
+
+        assert_eq!(layer.process(vec![0.4, 0.5]), vec![0.4, 0.5, 0.0]);
+        assert_eq!(layer.explain(), vec!["Rule 'confident_sum' output: 0.90 (gate off)"]);
+        assert_eq!(layer.backward(&[0.1, 0.2, 1.0]), vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_rule_weights_scale_outputs_and_learn() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule("sum", Box::new(|inputs: &[f64]| inputs.iter().sum()));
+        assert_eq!(layer.rule_weight("sum"), Some(1.0));
+        assert_eq!(layer.rule_weight("missing"), None);
+        assert!(!layer.set_rule_weight("missing", 2.0));
+
+        // d(loss)/d(weight) = error * rule output = 0.5 * 3.0
+        layer.process(vec![1.0, 2.0]);
+        let gradients = layer.backward(&[0.0, 0.0, 0.5]);
+        assert!(gradients.iter().all(|g| (g - 0.5).abs() < 1e-6));
+        layer.apply_gradients(0.1);
+        assert!((layer.rule_weight("sum").unwrap() - 0.85).abs() < 1e-12);
+
+        let output = layer.process(vec![1.0, 2.0]);
+        assert!((output[2] - 0.85 * 3.0).abs() < 1e-12);
+        let gradients = layer.backward(&[0.0, 0.0, 1.0]);
+        assert!(gradients.iter().all(|g| (g - 0.85).abs() < 1e-6));
+        assert_eq!(layer.evaluate(vec![1.0, 2.0]), output);
+    }
+
+    #[test]
+    fn test_backward_matches_across_repeated_calls() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule("product", Box::new(|inputs: &[f64]| inputs[0] * inputs[1]));
+        layer.add_rule_vec("squares", Box::new(|inputs: &[f64]| inputs.iter().map(|x| x * x).collect()));
+        layer.process(vec![2.0, 3.0]);
+
+        let error = [0.1, 0.2, 1.0, 0.5, -1.0];
+        let first = layer.backward(&error);
+        let second = layer.backward(&error);
+        assert_eq!(first, second);
+        // d/dx0: 0.1 + 1.0 * x1 + 0.5 * 2 * x0 = 5.1; d/dx1: 0.2 + 1.0 * x0 - 1.0 * 2 * x1 = -3.8
+        assert!((first[0] - 5.1).abs() < 1e-6);
+        assert!((first[1] + 3.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_builtin_rules_have_exact_gradients() {
+        let input = [2.0, -1.0, 0.0, 3.0];
+        assert_eq!(SymbolicRule::Sum.gradient(&input), vec![1.0; 4]);
+        assert_eq!(SymbolicRule::Mean.evaluate(&input), 1.0);
+        assert_eq!(SymbolicRule::Max.gradient(&input), vec![0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(SymbolicRule::Product.gradient(&input), vec![0.0, 0.0, -6.0, 0.0]);
+        assert_eq!(SymbolicRule::Max.evaluate(&[]), 0.0);
+
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_builtin_rule("product", SymbolicRule::Product);
+        assert_eq!(layer.process(vec![2.0, 3.0, 4.0]), vec![2.0, 3.0, 4.0, 24.0]);
+        assert_eq!(layer.backward(&[0.0, 0.0, 0.0, 1.0]), vec![12.0, 8.0, 6.0]);
+
+        layer.add_rule("product", Box::new(|inputs: &[f64]| inputs.iter().product()));
+        layer.process(vec![2.0, 3.0, 4.0]);
+        let approximate = layer.backward(&[0.0, 0.0, 0.0, 1.0]);
+        assert!(approximate.iter().zip([12.0, 8.0, 6.0]).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_builtin_rules_survive_serialization() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_builtin_rule("mean", SymbolicRule::Mean);
+        layer.add_rule("custom", Box::new(|inputs: &[f64]| inputs[0]));
+        let bytes = bincode::serialize(&layer).unwrap();
+        let mut restored: NeuroSymbolicLayer = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored.process(vec![1.0, 3.0]), vec![1.0, 3.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_relative_epsilon_keeps_quadratic_gradients_accurate() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule("square", Box::new(|inputs: &[f64]| inputs[0] * inputs[0]));
+        layer.set_epsilon(DEFAULT_EPSILON, 1e-6);
+        for x in [1e-3, 1e10] {
+            layer.process(vec![x]);
+            let gradient = layer.backward(&[0.0, 1.0])[0];
+            let analytic = 2.0 * x;
+            assert!((gradient - analytic).abs() <= 1e-9 * analytic, "x = {x}: {gradient} vs {analytic}");
+        }
+
+        // A fixed step comparable to the input's rounding error is far off.
+        layer.set_epsilon(DEFAULT_EPSILON, 0.0);
+        layer.process(vec![1e10]);
+        let gradient = layer.backward(&[0.0, 1.0])[0];
+        assert!((gradient - 2e10).abs() > 1e-3 * 2e10);
+    }
+
+    #[test]
+    fn test_remove_and_list_rules() {
+        let mut layer = NeuroSymbolicLayer::new();
+        layer.add_rule("sum", Box::new(|inputs: &[f64]| inputs.iter().sum()));
+        layer.add_rule("max", Box::new(|inputs: &[f64]| inputs.iter().cloned().fold(f64::MIN, f64::max)));
+        layer.add_rule("first", Box::new(|inputs: &[f64]| inputs[0]));
+        assert_eq!(layer.rule_names(), vec!["sum", "max", "first"]);
+
+        assert!(layer.remove_rule("max"));
+        assert!(!layer.remove_rule("max"));
+        assert!(!layer.has_rule("max"));
+        assert!(layer.has_rule("first"));
+        assert_eq!(layer.rule_names(), vec!["sum", "first"]);
+        assert_eq!(layer.process(vec![1.0, 2.0]), vec![1.0, 2.0, 3.0, 1.0]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+/// Update rule applied to a layer's weights given their gradients.
+///
+/// Each layer owns its own instance, so stateful optimizers keep per-weight
+/// state across training steps.
+pub trait Optimizer {
+    fn step(&mut self, weights: &mut [f64], grads: &[f64]);
+
+    fn learning_rate(&self) -> f64;
+
+    fn set_learning_rate(&mut self, learning_rate: f64);
+
+    /// Creates an optimizer with the same hyperparameters and empty state.
+    fn fresh(&self) -> Box<dyn Optimizer>;
+
+    /// Creates a copy including the current state.
+    fn clone_box(&self) -> Box<dyn Optimizer>;
+
+    /// Moves per-weight state to follow weights that were reordered, added
+    /// or removed: new weight `i` takes over the state of old weight
+    /// `sources[i]`, or starts fresh for `None`. Stateless optimizers keep
+    /// the default, which does nothing.
+    fn remap_state(&mut self, _sources: &[Option<usize>]) {}
+}
+
+impl Clone for Box<dyn Optimizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Scales `grads` down so its L2 norm is at most `max_norm`. Gradients already
+/// within the limit are left untouched.
+pub fn clip_grad_norm(grads: &mut [f64], max_norm: f64) {
+    // Normalise by the largest component first so the squared sum cannot overflow.
+    let largest = grads.iter().fold(0.0_f64, |max, g| max.max(g.abs()));
+    if largest == 0.0 || !largest.is_finite() {
+        return;
+    }
+    let norm = largest * grads.iter().map(|g| (g / largest).powi(2)).sum::<f64>().sqrt();
+    if norm > max_norm {
+        let scale = max_norm / norm;
+        for gradient in grads.iter_mut() {
+            *gradient *= scale;
+        }
+    }
+}
+
+/// Sources for [`Optimizer::remap_state`] when a layer's parameter vector is
+/// its row-major weight matrix of `old_shape` followed by one bias per row.
+/// New row `r` comes from old row `rows[r]`, or is new for `None`, and new
+/// column `c` from old column `cols[c]`.
+pub(crate) fn matrix_state_sources(old_shape: (usize, usize), rows: &[Option<usize>], cols: &[usize]) -> Vec<Option<usize>> {
+    let (old_rows, old_cols) = old_shape;
+    let weights = rows.iter().flat_map(|&row| cols.iter().map(move |&col| row.map(|row| row * old_cols + col)));
+    let biases = rows.iter().map(|&row| row.map(|row| old_rows * old_cols + row));
+    weights.chain(biases).collect()
+}
+
+pub(crate) fn default_optimizer() -> Box<dyn Optimizer> {
+    Box::new(Sgd::default())
+}
+
+/// Plain stochastic gradient descent: `weight -= learning_rate * gradient`.
+#[derive(Debug, Clone)]
+pub struct Sgd {
+    learning_rate: f64,
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f64) -> Self {
+        Sgd { learning_rate }
+    }
+}
+
+impl Default for Sgd {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, weights: &mut [f64], grads: &[f64]) {
+        for (weight, &gradient) in weights.iter_mut().zip(grads.iter()) {
+            *weight -= self.learning_rate * gradient;
+        }
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn fresh(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+
+    fn clone_box(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Adam with per-weight first and second moment estimates.
+///
+/// Layers that reorder, grow or prune their neurons carry the moments along
+/// through [`Optimizer::remap_state`]. If the number of weights changes
+/// without that, the moment buffers are only resized, and new entries start
+/// at zero.
+#[derive(Debug, Clone)]
+pub struct Adam {
+    learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    m: Vec<f64>,
+    v: Vec<f64>,
+    t: u64,
+}
+
+impl Adam {
+    pub fn new(learning_rate: f64) -> Self {
+        Self::with_params(learning_rate, 0.9, 0.999, 1e-8)
+    }
+
+    pub fn with_params(learning_rate: f64, beta1: f64, beta2: f64, epsilon: f64) -> Self {
+        Adam {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon,
+            m: Vec::new(),
+            v: Vec::new(),
+            t: 0,
+        }
+    }
+
+    /// Number of steps taken so far.
+    pub fn steps(&self) -> u64 {
+        self.t
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, weights: &mut [f64], grads: &[f64]) {
+        if self.m.len() != weights.len() {
+            self.m.resize(weights.len(), 0.0);
+            self.v.resize(weights.len(), 0.0);
+        }
+
+        self.t += 1;
+        let bias_correction1 = 1.0 - self.beta1.powi(self.t as i32);
+        let bias_correction2 = 1.0 - self.beta2.powi(self.t as i32);
+
+        for (i, (weight, &gradient)) in weights.iter_mut().zip(grads.iter()).enumerate() {
+            self.m[i] = self.beta1 * self.m[i] + (1.0 - self.beta1) * gradient;
+            self.v[i] = self.beta2 * self.v[i] + (1.0 - self.beta2) * gradient * gradient;
+            let m_hat = self.m[i] / bias_correction1;
+            let v_hat = self.v[i] / bias_correction2;
+            *weight -= self.learning_rate * m_hat / (v_hat.sqrt() + self.epsilon);
+        }
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn fresh(&self) -> Box<dyn Optimizer> {
+        Box::new(Adam::with_params(self.learning_rate, self.beta1, self.beta2, self.epsilon))
+    }
+
+    fn clone_box(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+
+    fn remap_state(&mut self, sources: &[Option<usize>]) {
+        if self.m.is_empty() {
+            return;
+        }
+        let remap = |state: &[f64]| -> Vec<f64> {
+            sources.iter().map(|source| source.and_then(|i| state.get(i).copied()).unwrap_or(0.0)).collect()
+        };
+        self.m = remap(&self.m);
+        self.v = remap(&self.v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sgd_step() {
+        let mut sgd = Sgd::new(0.5);
+        let mut weights = vec![1.0, -1.0];
+        sgd.step(&mut weights, &[2.0, -2.0]);
+        assert_eq!(weights, vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_adam_state_persists_across_steps() {
+        let mut adam = Adam::new(0.1);
+        let mut weights = vec![3.0];
+        for _ in 0..200 {
+            let grads = vec![2.0 * weights[0]];
+            adam.step(&mut weights, &grads);
+        }
+        assert_eq!(adam.steps(), 200);
+        assert!(weights[0].abs() < 0.1);
+    }
+
+    #[test]
+    fn test_adam_moments_follow_remapped_weights() {
+        let mut adam = Adam::new(0.1);
+        let mut weights = vec![1.0, 2.0, 3.0];
+        adam.step(&mut weights, &[0.1, 0.2, 0.3]);
+        let (m, v) = (adam.m.clone(), adam.v.clone());
+
+        adam.remap_state(&[Some(2), None, Some(0)]);
+        assert_eq!(adam.m, vec![m[2], 0.0, m[0]]);
+        assert_eq!(adam.v, vec![v[2], 0.0, v[0]]);
+        // Two rows of two weights, then two biases: drop column 0 and swap the rows.
+        let sources = matrix_state_sources((2, 2), &[Some(1), Some(0)], &[1]);
+        assert_eq!(sources, vec![Some(3), Some(1), Some(5), Some(4)]);
+    }
+
+    #[test]
+    fn test_clip_grad_norm() {
+        let mut grads = vec![3e200, 4e200];
+        clip_grad_norm(&mut grads, 1.0);
+        assert!((grads[0] - 0.6).abs() < 1e-12 && (grads[1] - 0.8).abs() < 1e-12);
+
+        let mut small = vec![0.3, 0.4];
+        clip_grad_norm(&mut small, 1.0);
+        assert_eq!(small, vec![0.3, 0.4]);
+    }
+}
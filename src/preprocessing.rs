@@ -0,0 +1,62 @@
+/// Rescales every column of `data` into `[0, 1]` by min-max normalization and
+/// returns the `(min, max)` of each column, for use with
+/// [`apply_normalization`] on further data. Columns holding a single value
+/// become all zeros. Rows are expected to have the same length as the first.
+pub fn normalize_columns(data: &mut [Vec<f64>]) -> Vec<(f64, f64)> {
+    let columns = data.first().map_or(0, Vec::len);
+    let ranges: Vec<(f64, f64)> = (0..columns)
+        .map(|column| {
+            data.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), row| {
+                (min.min(row[column]), max.max(row[column]))
+            })
+        })
+        .collect();
+    apply_normalization(data, &ranges);
+    ranges
+}
+
+/// Rescales `data` with the column ranges returned by [`normalize_columns`].
+/// Values outside a range map outside `[0, 1]`.
+pub fn apply_normalization(data: &mut [Vec<f64>], ranges: &[(f64, f64)]) {
+    for row in data.iter_mut() {
+        for (value, &(min, max)) in row.iter_mut().zip(ranges.iter()) {
+            let span = max - min;
+            *value = if span > 0.0 { (*value - min) / span } else { 0.0 };
+        }
+    }
+}
+
+/// One target vector per label, with 1.0 at the label's index and 0.0
+/// elsewhere. Panics if a label is not below `num_classes`.
+pub fn one_hot(labels: &[usize], num_classes: usize) -> Vec<Vec<f64>> {
+    labels.iter()
+        .map(|&label| {
+            assert!(label < num_classes, "label {} is out of range for {} classes", label, num_classes);
+            let mut encoded = vec![0.0; num_classes];
+            encoded[label] = 1.0;
+            encoded
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_round_trip_on_test_data() {
+        let mut train = vec![vec![0.0, 5.0, 2.0], vec![10.0, 5.0, 4.0], vec![5.0, 5.0, 3.0]];
+        let ranges = normalize_columns(&mut train);
+        assert_eq!(ranges, vec![(0.0, 10.0), (5.0, 5.0), (2.0, 4.0)]);
+        assert_eq!(train, vec![vec![0.0, 0.0, 0.0], vec![1.0, 0.0, 1.0], vec![0.5, 0.0, 0.5]]);
+
+        let mut test = vec![vec![20.0, 7.0, 3.0]];
+        apply_normalization(&mut test, &ranges);
+        assert_eq!(test, vec![vec![2.0, 0.0, 0.5]]);
+    }
+
+    #[test]
+    fn test_one_hot() {
+        assert_eq!(one_hot(&[2, 0], 3), vec![vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0]]);
+    }
+}
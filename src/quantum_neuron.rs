@@ -1,41 +1,461 @@
 use rand::Rng;
 use std::f64::consts::PI;
 
+/// How a [`QuantumNeuron`] decides when and how to collapse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuantumMode {
+    /// Measures with probability `emotional_state`, collapsing to `|1>` with
+    /// probability `beta^2`.
+    #[default]
+    Stochastic,
+    /// Measures iff `emotional_state > 0.5`, collapsing to whichever basis state
+    /// is more likely. No randomness is drawn.
+    ///
+    /// In both modes `emotional_state` is first scaled by the neuron's
+    /// [superposition sensitivity](QuantumNeuron::set_superposition_sensitivity).
+    Deterministic,
+}
+
+/// A two-amplitude qubit-like neuron with state `alpha|0> + beta|1>`, where the
+/// real amplitudes always satisfy `alpha^2 + beta^2 == 1`.
+///
+/// Neurons can be [entangled](QuantumNeuron::entangle) in pairs. This is a
+/// loose analogy to a Bell pair rather than a physical model: whenever one of
+/// them collapses, its owner hands the outcome to the partner through
+/// [`QuantumNeuron::receive_collapse`], and the partner starts its next
+/// [`QuantumNeuron::activate`] from the same basis state instead of its own
+/// superposition.
+///
+/// A superposed neuron outputs `mix * beta + (1 - mix) * alpha`, in `[-1, 1]`,
+/// where the [mix](QuantumNeuron::set_mix) defaults to 1, i.e. the `beta`
+/// amplitude alone. Built with `unit_output`, it outputs `(x + 1) / 2` of that
+/// value `x` instead, so that together with the measured values 0 and 1 every
+/// output lies in `[0, 1]`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuantumNeuron {
-    phase: f64,
-    superposition: bool,
+    alpha: f64,
+    beta: f64,
+    // d(output)/d(input) of the last `activate`, taken through the rotation
+    // before any measurement.
+    output_derivative: f64,
+    // d(output)/d(mix) of the last `activate`, likewise before any measurement.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mix_derivative: f64,
+    mode: QuantumMode,
+    #[cfg_attr(feature = "serde", serde(default))]
+    entangled: bool,
+    // Outcome the last `activate` collapsed to, if it did.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_collapse: Option<f64>,
+    // Outcome of the partner's collapse, applied at the start of the next `activate`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pending_collapse: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    unit_output: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_one"))]
+    superposition_sensitivity: f64,
+    #[cfg_attr(feature = "serde", serde(default = "default_one"))]
+    mix: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    learn_mix: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_one() -> f64 {
+    1.0
+}
+
+impl Default for QuantumNeuron {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl QuantumNeuron {
+    /// Creates a stochastic neuron in the `|0>` state.
     pub fn new() -> Self {
+        Self::with_mode(QuantumMode::default())
+    }
+
+    pub fn with_mode(mode: QuantumMode) -> Self {
+        Self::with_options(mode, false)
+    }
+
+    /// Creates a neuron in the `|0>` state whose superposed output is
+    /// rescaled into `[0, 1]` when `unit_output` is set.
+    pub fn with_options(mode: QuantumMode, unit_output: bool) -> Self {
         QuantumNeuron {
-            phase: 0.0,
-            superposition: false,
+            alpha: 1.0,
+            beta: 0.0,
+            output_derivative: 0.0,
+            mix_derivative: 0.0,
+            mode,
+            entangled: false,
+            last_collapse: None,
+            pending_collapse: None,
+            unit_output,
+            superposition_sensitivity: 1.0,
+            mix: 1.0,
+            learn_mix: false,
         }
     }
 
-    pub fn activate(&mut self, input: f64, emotional_state: f64) -> f64 {
-        let mut rng = rand::thread_rng();
-        
-        self.phase += input * PI * 2.0;
-        self.phase %= 2.0 * PI;
+    pub fn mode(&self) -> QuantumMode {
+        self.mode
+    }
 
-        if rng.gen::<f64>() < emotional_state {
-            self.superposition = !self.superposition;
-        }
+    /// Factor applied to `emotional_state` before it decides whether
+    /// [`QuantumNeuron::activate`] measures; the scaled state is clamped into
+    /// `[0, 1]`. The default of 1 uses the emotional state as it is, and 0
+    /// keeps the neuron from ever measuring on its own.
+    pub fn set_superposition_sensitivity(&mut self, sensitivity: f64) {
+        self.superposition_sensitivity = sensitivity;
+    }
+
+    pub fn superposition_sensitivity(&self) -> f64 {
+        self.superposition_sensitivity
+    }
+
+    /// Weight of `beta` against `alpha` in the superposed output.
+    pub fn mix(&self) -> f64 {
+        self.mix
+    }
+
+    /// Sets the weight of `beta` against `alpha` in the superposed output, in
+    /// `[0, 1]`: 1, the default, outputs `beta` and 0.5 the mean of both.
+    pub fn set_mix(&mut self, mix: f64) {
+        assert!((0.0..=1.0).contains(&mix), "mix must lie in [0, 1]");
+        self.mix = mix;
+    }
+
+    /// Enables or disables learning the mix in
+    /// [`QuantumNeuron::update_mix`]. Off by default.
+    pub fn set_learn_mix(&mut self, enabled: bool) {
+        self.learn_mix = enabled;
+    }
+
+    /// Whether superposed outputs are rescaled into `[0, 1]`.
+    pub fn unit_output(&self) -> bool {
+        self.unit_output
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn beta(&self) -> f64 {
+        self.beta
+    }
+
+    /// Rotation angle of the state, `atan2(beta, alpha)`, in `(-PI, PI]`.
+    pub fn phase(&self) -> f64 {
+        self.beta.atan2(self.alpha)
+    }
+
+    /// Whether the state is a superposition rather than a basis state.
+    pub fn is_superposed(&self) -> bool {
+        self.alpha != 0.0 && self.beta != 0.0
+    }
+
+    /// Marks this neuron and `other` as an entangled pair. Passing collapses
+    /// between them is left to their owner, e.g. a quantum layer's
+    /// entangled pairs.
+    pub fn entangle(&mut self, other: &mut QuantumNeuron) {
+        self.entangled = true;
+        other.entangled = true;
+    }
+
+    pub fn is_entangled(&self) -> bool {
+        self.entangled
+    }
+
+    /// The basis state (0 or 1) the last [`QuantumNeuron::activate`] collapsed
+    /// to, or `None` if it returned a superposition.
+    pub fn last_collapse(&self) -> Option<f64> {
+        self.last_collapse
+    }
 
-        if self.superposition {
-            (self.phase.sin() + self.phase.cos()) / 2.0
+    /// Records that the entangled partner collapsed to `outcome`; the next
+    /// [`QuantumNeuron::activate`] starts from that basis state.
+    pub fn receive_collapse(&mut self, outcome: f64) {
+        self.pending_collapse = Some(outcome);
+    }
+
+    /// Returns the neuron to the `|0>` state, i.e. a phase of zero, and drops
+    /// any collapse received from an entangled partner.
+    pub fn reset_phase(&mut self) {
+        self.alpha = 1.0;
+        self.beta = 0.0;
+        self.pending_collapse = None;
+        self.last_collapse = None;
+    }
+
+    /// The superposed output [`QuantumNeuron::activate`] would return for
+    /// `input`, without measuring or changing the neuron's state.
+    pub fn peek(&self, input: f64) -> f64 {
+        let (sin, cos) = (input * PI * 2.0).sin_cos();
+        let alpha = self.alpha * cos - self.beta * sin;
+        let beta = self.alpha * sin + self.beta * cos;
+        let norm = alpha.hypot(beta);
+        self.superposed_output(alpha / norm, beta / norm)
+    }
+
+    fn superposed_output(&self, alpha: f64, beta: f64) -> f64 {
+        let mixed = self.mix * beta + (1.0 - self.mix) * alpha;
+        if self.unit_output {
+            (mixed + 1.0) / 2.0
         } else {
-            self.phase.sin()
+            mixed
         }
     }
 
+    /// Rotates the amplitudes by `2 * PI * input` and returns the superposed
+    /// output described on [`QuantumNeuron`]. If the
+    /// neuron's [`QuantumMode`] decides to measure, the state is collapsed and
+    /// the measured value (0 or 1) is returned instead.
+    pub fn activate<R: Rng + ?Sized>(&mut self, input: f64, emotional_state: f64, rng: &mut R) -> f64 {
+        if let Some(outcome) = self.pending_collapse.take() {
+            self.collapse(outcome);
+        }
+        self.last_collapse = None;
+        let (sin, cos) = (input * PI * 2.0).sin_cos();
+        let alpha = self.alpha * cos - self.beta * sin;
+        let beta = self.alpha * sin + self.beta * cos;
+        let norm = alpha.hypot(beta);
+        self.alpha = alpha / norm;
+        self.beta = beta / norm;
+        // d(beta)/d(input) is 2 PI alpha and d(alpha)/d(input) is -2 PI beta.
+        let scale = if self.unit_output { 0.5 } else { 1.0 };
+        self.output_derivative = scale * 2.0 * PI * (self.mix * self.alpha - (1.0 - self.mix) * self.beta);
+        self.mix_derivative = scale * (self.beta - self.alpha);
+
+        let coupling = (emotional_state * self.superposition_sensitivity).clamp(0.0, 1.0);
+        match self.mode {
+            QuantumMode::Stochastic if rng.gen::<f64>() < coupling => self.measure_with_rng(rng),
+            QuantumMode::Deterministic if coupling > 0.5 => {
+                let outcome = if self.beta * self.beta > 0.5 { 1.0 } else { 0.0 };
+                self.collapse(outcome)
+            }
+            _ => self.superposed_output(self.alpha, self.beta),
+        }
+    }
+
+    /// Collapses the state to `|1>` with probability `beta^2`, otherwise to `|0>`,
+    /// and returns the measured value.
+    pub fn measure(&mut self) -> f64 {
+        self.measure_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn measure_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) -> f64 {
+        let outcome = if rng.gen::<f64>() < self.beta * self.beta { 1.0 } else { 0.0 };
+        self.collapse(outcome)
+    }
+
+    fn collapse(&mut self, outcome: f64) -> f64 {
+        self.alpha = 1.0 - outcome;
+        self.beta = outcome;
+        self.last_collapse = Some(outcome);
+        outcome
+    }
+
+    /// Whether both amplitudes are finite numbers.
+    pub fn is_finite(&self) -> bool {
+        self.alpha.is_finite() && self.beta.is_finite()
+    }
+
+    /// Backpropagates `error` through the last rotation. A measurement is treated
+    /// as passing the gradient straight through.
     pub fn calculate_gradient(&self, error: f64) -> f64 {
-        if self.superposition {
-            error * (self.phase.cos() - self.phase.sin()) / 2.0
-        } else {
-            error * self.phase.cos()
+        error * self.output_derivative
+    }
+
+    /// Gradient of the mix for `error` at the last output, passed straight
+    /// through a measurement like [`QuantumNeuron::calculate_gradient`].
+    pub fn mix_gradient(&self, error: f64) -> f64 {
+        error * self.mix_derivative
+    }
+
+    /// Moves the mix against `gradient` by `learning_rate`, keeping it within
+    /// `[0, 1]`, if the neuron learns its mix; otherwise does nothing.
+    pub fn update_mix(&mut self, gradient: f64, learning_rate: f64) {
+        if self.learn_mix {
+            self.mix = (self.mix - learning_rate * gradient).clamp(0.0, 1.0);
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_rotation_keeps_amplitudes_normalized() {
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let mut neuron = QuantumNeuron::new();
+        for &input in &[0.1, 0.37, -0.2, 5.3] {
+            let output = neuron.activate(input, 0.0, &mut rng);
+            assert_eq!(output, neuron.beta());
+            assert!((neuron.alpha().powi(2) + neuron.beta().powi(2) - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_measure_collapses_to_basis_state() {
+        let mut rng = ChaCha12Rng::seed_from_u64(2);
+        let mut neuron = QuantumNeuron::new();
+        neuron.activate(0.125, 0.0, &mut rng);
+        let outcome = neuron.measure_with_rng(&mut rng);
+        assert!(outcome == 0.0 || outcome == 1.0);
+        assert_eq!((neuron.alpha(), neuron.beta()), (1.0 - outcome, outcome));
+
+        // A collapsed basis state measures the same way every time.
+        for _ in 0..10 {
+            assert_eq!(neuron.measure_with_rng(&mut rng), outcome);
+        }
+        assert!(!neuron.is_superposed());
+    }
+
+    #[test]
+    fn test_deterministic_mode_uses_threshold() {
+        let mut rng = ChaCha12Rng::seed_from_u64(5);
+        let mut neuron = QuantumNeuron::with_mode(QuantumMode::Deterministic);
+        // beta^2 = sin^2(0.4 PI) > 0.5, but 0.5 is not above the threshold.
+        let output = neuron.activate(0.2, 0.5, &mut rng);
+        assert_eq!(output, (0.4 * PI).sin());
+
+        neuron.reset_phase();
+        assert_eq!(neuron.activate(0.2, 0.51, &mut rng), 1.0);
+        assert_eq!((neuron.alpha(), neuron.beta()), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_zero_sensitivity_never_measures() {
+        let mut rng = ChaCha12Rng::seed_from_u64(8);
+        for mode in [QuantumMode::Stochastic, QuantumMode::Deterministic] {
+            let mut neuron = QuantumNeuron::with_mode(mode);
+            neuron.set_superposition_sensitivity(0.0);
+            for &input in &[0.1, 0.37, -0.2] {
+                assert_eq!(neuron.activate(input, 1.0, &mut rng), neuron.beta());
+                assert_eq!(neuron.last_collapse(), None);
+            }
+        }
+
+        // Doubled, an emotional state of 0.3 crosses the deterministic threshold.
+        let mut neuron = QuantumNeuron::with_mode(QuantumMode::Deterministic);
+        neuron.set_superposition_sensitivity(2.0);
+        assert_eq!(neuron.activate(0.2, 0.3, &mut rng), 1.0);
+        assert_eq!(neuron.last_collapse(), Some(1.0));
+    }
+
+    #[test]
+    fn test_received_collapse_sets_the_next_starting_state() {
+        let mut rng = ChaCha12Rng::seed_from_u64(6);
+        let mut first = QuantumNeuron::with_mode(QuantumMode::Deterministic);
+        let mut second = QuantumNeuron::with_mode(QuantumMode::Deterministic);
+        first.entangle(&mut second);
+        assert!(first.is_entangled() && second.is_entangled());
+
+        assert_eq!(first.activate(0.2, 1.0, &mut rng), 1.0);
+        assert_eq!(first.last_collapse(), Some(1.0));
+        second.receive_collapse(1.0);
+
+        // Starting from |1>, a zero rotation leaves beta at 1.
+        assert_eq!(second.activate(0.0, 0.0, &mut rng), 1.0);
+        assert_eq!(second.last_collapse(), None);
+    }
+
+    #[test]
+    fn test_unit_output_stays_within_zero_and_one() {
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        let mut neuron = QuantumNeuron::with_options(QuantumMode::Stochastic, true);
+        for &input in &[0.1, 0.37, -0.2, 5.3, 0.75] {
+            assert_eq!(neuron.peek(input), neuron.clone().activate(input, 0.0, &mut rng));
+            let output = neuron.activate(input, 0.5, &mut rng);
+            assert!((0.0..=1.0).contains(&output), "{output}");
+            if neuron.last_collapse().is_none() {
+                assert_eq!(output, (neuron.beta() + 1.0) / 2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_phase_tracks_rotation() {
+        let mut rng = ChaCha12Rng::seed_from_u64(4);
+        let mut neuron = QuantumNeuron::new();
+        assert_eq!(neuron.phase(), 0.0);
+        neuron.activate(0.1, 0.0, &mut rng);
+        neuron.activate(0.05, 0.0, &mut rng);
+        assert!((neuron.phase() - 0.3 * PI).abs() < 1e-12);
+        assert!(neuron.is_superposed());
+
+        neuron.reset_phase();
+        assert_eq!(neuron.phase(), 0.0);
+        assert!(!neuron.is_superposed());
+    }
+
+    #[test]
+    fn test_gradient_matches_finite_difference() {
+        let mut rng = ChaCha12Rng::seed_from_u64(3);
+        let mut start = QuantumNeuron::new();
+        start.activate(0.3, 0.0, &mut rng);
+
+        let input = 0.05;
+        let epsilon = 1e-6;
+        let mut neuron = start.clone();
+        neuron.activate(input, 0.0, &mut rng);
+        let analytic = neuron.calculate_gradient(1.0);
+        let pos = start.clone().activate(input + epsilon, 0.0, &mut rng);
+        let neg = start.clone().activate(input - epsilon, 0.0, &mut rng);
+        assert!((analytic - (pos - neg) / (2.0 * epsilon)).abs() < 1e-6);
+
+        let unit = QuantumNeuron::with_options(QuantumMode::Stochastic, true);
+        let numeric = (unit.peek(input + epsilon) - unit.peek(input - epsilon)) / (2.0 * epsilon);
+        let mut activated = unit.clone();
+        activated.activate(input, 0.0, &mut rng);
+        assert!((activated.calculate_gradient(1.0) - numeric).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mixed_output_gradients_match_finite_differences() {
+        let mut rng = ChaCha12Rng::seed_from_u64(9);
+        let input = 0.15;
+        let epsilon = 1e-6;
+        for unit_output in [false, true] {
+            let mut start = QuantumNeuron::with_options(QuantumMode::Stochastic, unit_output);
+            start.set_mix(0.3);
+            start.activate(0.2, 0.0, &mut rng);
+
+            let mut neuron = start.clone();
+            let output = neuron.activate(input, 0.0, &mut rng);
+            let (alpha, beta) = (neuron.alpha(), neuron.beta());
+            let mixed = 0.3 * beta + 0.7 * alpha;
+            assert!((output - if unit_output { (mixed + 1.0) / 2.0 } else { mixed }).abs() < 1e-12);
+
+            let numeric = (start.peek(input + epsilon) - start.peek(input - epsilon)) / (2.0 * epsilon);
+            assert!((neuron.calculate_gradient(1.0) - numeric).abs() < 1e-6);
+            let peek_with_mix = |mix: f64| {
+                let mut shifted = start.clone();
+                shifted.set_mix(mix);
+                shifted.peek(input)
+            };
+            let numeric = (peek_with_mix(0.3 + epsilon) - peek_with_mix(0.3 - epsilon)) / (2.0 * epsilon);
+            assert!((neuron.mix_gradient(1.0) - numeric).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_mix_only_moves_when_learned() {
+        let mut neuron = QuantumNeuron::new();
+        neuron.update_mix(0.5, 0.1);
+        assert_eq!(neuron.mix(), 1.0);
+        neuron.set_learn_mix(true);
+        neuron.update_mix(0.5, 0.1);
+        assert!((neuron.mix() - 0.95).abs() < 1e-12);
+        neuron.update_mix(-5.0, 1.0);
+        assert_eq!(neuron.mix(), 1.0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+/// A queue holding at most `capacity` items, which drops its oldest item to
+/// make room for a new one. Used for the activation histories and the
+/// emotional memory.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// An empty buffer. A capacity of 0 keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer { items: VecDeque::new(), capacity }
+    }
+
+    /// Appends `item` as the newest entry, returning the oldest one if the
+    /// buffer was full.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(item);
+        }
+        let evicted = if self.is_full() { self.items.pop_front() } else { None };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+
+    /// The newest entry.
+    pub fn back(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Entries from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        self.items.iter_mut()
+    }
+
+    /// Removes the entry `index` places after the oldest one.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.items.remove(index)
+    }
+
+    /// Keeps only the entries for which `keep` returns true, in order.
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.items.retain(keep);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl RingBuffer<f64> {
+    /// Mean of the entries, or `None` when the buffer is empty.
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.items.iter().sum::<f64>() / self.len() as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_evicts_the_oldest_entry_once_full() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.mean(), None);
+        for value in [1.0, 2.0, 3.0] {
+            assert_eq!(buffer.push(value), None);
+        }
+        assert!(buffer.is_full());
+        assert_eq!(buffer.push(4.0), Some(1.0));
+        assert_eq!(buffer.iter().copied().collect::<Vec<f64>>(), vec![2.0, 3.0, 4.0]);
+        assert_eq!(buffer.mean(), Some(3.0));
+
+        let mut empty = RingBuffer::new(0);
+        assert_eq!(empty.push(1.0), Some(1.0));
+        assert!(empty.is_empty());
+    }
+}
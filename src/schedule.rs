@@ -0,0 +1,73 @@
+use std::f64::consts::PI;
+
+/// Learning rate as a function of the epoch index, evaluated at the start of
+/// every epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LrSchedule {
+    Constant(f64),
+    /// Multiplies the rate by `factor` every `every` epochs.
+    StepDecay { initial: f64, factor: f64, every: usize },
+    /// `initial * gamma^epoch`
+    ExponentialDecay { initial: f64, gamma: f64 },
+    /// Anneals from `initial` to zero over `epochs` along half a cosine period.
+    CosineAnnealing { initial: f64, epochs: usize },
+}
+
+impl LrSchedule {
+    pub fn learning_rate(&self, epoch: usize) -> f64 {
+        match *self {
+            LrSchedule::Constant(rate) => rate,
+            LrSchedule::StepDecay { initial, factor, every } => initial * factor.powi((epoch / every.max(1)) as i32),
+            LrSchedule::ExponentialDecay { initial, gamma } => initial * gamma.powi(epoch as i32),
+            LrSchedule::CosineAnnealing { initial, epochs } => {
+                let progress = epoch.min(epochs) as f64 / epochs.max(1) as f64;
+                0.5 * initial * (1.0 + (PI * progress).cos())
+            }
+        }
+    }
+
+    /// Rate at `epoch` relative to the rate at epoch 0, used to scale
+    /// per-layer rates. A schedule that starts at zero has no first rate to
+    /// be relative to, so its rate is returned unchanged.
+    pub fn factor(&self, epoch: usize) -> f64 {
+        let first = self.learning_rate(0);
+        if first == 0.0 {
+            self.learning_rate(epoch)
+        } else {
+            self.learning_rate(epoch) / first
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_decay() {
+        let schedule = LrSchedule::StepDecay { initial: 1.0, factor: 0.5, every: 10 };
+        assert_eq!(schedule.learning_rate(0), 1.0);
+        assert_eq!(schedule.learning_rate(9), 1.0);
+        assert_eq!(schedule.learning_rate(10), 0.5);
+        assert_eq!(schedule.learning_rate(25), 0.25);
+    }
+
+    #[test]
+    fn test_factor_is_relative_to_the_first_epoch() {
+        let schedule = LrSchedule::StepDecay { initial: 0.2, factor: 0.5, every: 1 };
+        assert_eq!(schedule.factor(0), 1.0);
+        assert_eq!(schedule.factor(2), 0.25);
+        assert_eq!(LrSchedule::Constant(0.0).factor(3), 0.0);
+        assert_eq!(LrSchedule::CosineAnnealing { initial: 0.0, epochs: 10 }.factor(0), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_annealing_endpoints() {
+        let schedule = LrSchedule::CosineAnnealing { initial: 0.1, epochs: 100 };
+        assert!((schedule.learning_rate(0) - 0.1).abs() < 1e-12);
+        assert!((schedule.learning_rate(50) - 0.05).abs() < 1e-12);
+        assert!(schedule.learning_rate(100).abs() < 1e-12);
+        assert!(schedule.learning_rate(150).abs() < 1e-12);
+    }
+}
@@ -1,47 +1,291 @@
 use rand::Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
+use crate::activation::sigmoid;
+use crate::config::DEFAULT_HISTORY_LENGTH;
+use crate::init::WeightInit;
+use crate::optimizer::{clip_grad_norm, default_optimizer, matrix_state_sources, Optimizer};
+use crate::ring_buffer::RingBuffer;
 
+/// Time constant of the exponential STDP window: an input arriving `dt` before
+/// or after the neuron fires changes its weight in proportion to `exp(-|dt| / STDP_TAU)`.
+const STDP_TAU: f64 = 1.0;
+
+/// Activation above which a neuron counts as firing for STDP.
+const STDP_FIRING_THRESHOLD: f64 = 0.5;
+
+/// Upper bound on a neuron's plasticity when it is learned.
+const MAX_PLASTICITY: f64 = 1.0;
+
+/// `(min_delay, max_delay)` used unless a neuron is built with
+/// [`TemporalNeuron::with_delay_range`].
+pub const DEFAULT_DELAY_RANGE: (f64, f64) = (0.0, 1.0);
+
+/// Weighting applied to an input according to how long ago it arrived
+/// (`time - delay`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemporalKernel {
+    /// `exp(-|t| / tau)`
+    Exponential { tau: f64 },
+    /// `exp(-t^2 / (2 sigma^2))`
+    Gaussian { sigma: f64 },
+    /// `(t / tau) exp(1 - t / tau)` for `t >= 0`, zero before; peaks at 1 when `t == tau`.
+    Alpha { tau: f64 },
+}
+
+impl Default for TemporalKernel {
+    fn default() -> Self {
+        TemporalKernel::Exponential { tau: 1.0 }
+    }
+}
+
+impl TemporalKernel {
+    pub fn evaluate(&self, t: f64) -> f64 {
+        match *self {
+            TemporalKernel::Exponential { tau } => (-t.abs() / tau).exp(),
+            TemporalKernel::Gaussian { sigma } => (-t * t / (2.0 * sigma * sigma)).exp(),
+            TemporalKernel::Alpha { tau } => {
+                if t < 0.0 {
+                    0.0
+                } else {
+                    (t / tau) * (1.0 - t / tau).exp()
+                }
+            }
+        }
+    }
+
+    /// Derivative of [`TemporalKernel::evaluate`] with respect to `t`. Where the
+    /// kernel has a kink (the exponential at 0, the alpha kernel's onset), the
+    /// derivative is taken as 0.
+    pub fn derivative(&self, t: f64) -> f64 {
+        match *self {
+            TemporalKernel::Exponential { tau } => {
+                if t == 0.0 {
+                    0.0
+                } else {
+                    -t.signum() / tau * self.evaluate(t)
+                }
+            }
+            TemporalKernel::Gaussian { sigma } => -t / (sigma * sigma) * self.evaluate(t),
+            TemporalKernel::Alpha { tau } => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    (1.0 - t / tau) * (1.0 - t / tau).exp() / tau
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TemporalNeuron {
     weights: Vec<f64>,
+    bias: f64,
     delays: Vec<f64>,
-    activation_history: Vec<(f64, f64)>, // (time, activation)
+    // Delays are drawn from and kept within `min..=max`.
+    #[cfg_attr(feature = "serde", serde(default = "default_delay_range"))]
+    delay_range: (f64, f64),
+    activation_history: RingBuffer<(f64, f64)>, // (time, activation)
+    // Input of the last activation, which the weight gradients are taken against.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_input: Vec<f64>,
     plasticity: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    learn_plasticity: bool,
+    // Delay gradients of the previous `update_delays`, for learning plasticity.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    previous_delay_gradients: Vec<f64>,
+    kernel: TemporalKernel,
+    // After an activation above `fire_threshold`, outputs are held at 0 for
+    // `refractory_period` time units; a period of 0 disables this.
+    #[cfg_attr(feature = "serde", serde(default))]
+    refractory_period: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    fire_threshold: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    last_fire_time: Option<f64>,
+}
+
+#[cfg(feature = "serde")]
+fn default_delay_range() -> (f64, f64) {
+    DEFAULT_DELAY_RANGE
 }
 
 impl TemporalNeuron {
     pub fn new(input_size: usize) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::with_rng(input_size, TemporalKernel::default(), &mut rand::thread_rng())
+    }
+
+    pub fn with_rng<R: Rng + ?Sized>(input_size: usize, kernel: TemporalKernel, rng: &mut R) -> Self {
+        Self::with_init(input_size, input_size, kernel, WeightInit::default(), DEFAULT_HISTORY_LENGTH, rng)
+    }
+
+    /// Like [`TemporalNeuron::with_rng`], drawing the initial weights from
+    /// `init` and keeping the last `history_length` activations, at least 1.
+    /// `fan_out` is the width of the layer the neuron belongs to.
+    pub fn with_init<R: Rng + ?Sized>(
+        input_size: usize,
+        fan_out: usize,
+        kernel: TemporalKernel,
+        init: WeightInit,
+        history_length: usize,
+        rng: &mut R,
+    ) -> Self {
+        Self::with_delay_range(input_size, fan_out, kernel, init, history_length, DEFAULT_DELAY_RANGE, rng)
+    }
+
+    /// Like [`TemporalNeuron::with_init`], drawing the initial delays from
+    /// `delay_range` instead of [`DEFAULT_DELAY_RANGE`] and keeping them
+    /// within it during training. Delays are in the same time units as the
+    /// timestamps passed to `activate`, so slow processes need a wider range.
+    /// Panics if the minimum is above the maximum.
+    pub fn with_delay_range<R: Rng + ?Sized>(
+        input_size: usize,
+        fan_out: usize,
+        kernel: TemporalKernel,
+        init: WeightInit,
+        history_length: usize,
+        delay_range: (f64, f64),
+        rng: &mut R,
+    ) -> Self {
+        let (min_delay, max_delay) = delay_range;
+        assert!(min_delay <= max_delay, "min_delay must not be above max_delay");
+        let history_length = history_length.max(1);
         TemporalNeuron {
-            weights: (0..input_size).map(|_| rng.gen_range(-1.0..1.0)).collect(),
-            delays: (0..input_size).map(|_| rng.gen_range(0.0..1.0)).collect(),
-            activation_history: Vec::new(),
+            weights: (0..input_size).map(|_| init.sample(input_size, fan_out, rng)).collect(),
+            bias: 0.0,
+            delays: (0..input_size)
+                .map(|_| if min_delay < max_delay { rng.gen_range(min_delay..max_delay) } else { min_delay })
+                .collect(),
+            delay_range,
+            activation_history: RingBuffer::new(history_length),
+            last_input: vec![0.0; input_size],
             plasticity: rng.gen_range(0.0..0.1),
+            learn_plasticity: false,
+            previous_delay_gradients: Vec::new(),
+            kernel,
+            refractory_period: 0.0,
+            fire_threshold: STDP_FIRING_THRESHOLD,
+            last_fire_time: None,
         }
     }
 
+    /// Makes the neuron spiking-like: once an activation exceeds
+    /// `fire_threshold` at time `t`, every activation at a time in
+    /// `t..t + refractory_period` is suppressed to 0 and passes no gradient
+    /// back. A period of 0, the default, never suppresses.
+    pub fn set_refractory(&mut self, refractory_period: f64, fire_threshold: f64) {
+        assert!(refractory_period >= 0.0, "refractory_period must not be negative");
+        self.refractory_period = refractory_period;
+        self.fire_threshold = fire_threshold;
+        self.last_fire_time = None;
+    }
+
+    pub fn refractory_period(&self) -> f64 {
+        self.refractory_period
+    }
+
+    /// Whether an activation at `time` falls within the refractory period of
+    /// the last time the neuron fired.
+    pub fn is_refractory(&self, time: f64) -> bool {
+        self.last_fire_time.is_some_and(|fired| (0.0..self.refractory_period).contains(&(time - fired)))
+    }
+
     pub fn activate(&mut self, input: &[f64], time: f64) -> f64 {
+        self.last_input.clear();
+        self.last_input.extend_from_slice(input);
+        let activation = self.output(input, time);
+        if self.refractory_period > 0.0 && !self.is_refractory(time) && activation > self.fire_threshold {
+            self.last_fire_time = Some(time);
+        }
+        self.activation_history.push((time, activation));
+        
+        activation
+    }
+
+    /// The activation [`TemporalNeuron::activate`] would return, without
+    /// recording it.
+    pub fn output(&self, input: &[f64], time: f64) -> f64 {
+        if self.is_refractory(time) {
+            // The sigmoid's derivative vanishes at 0, so a suppressed step
+            // also contributes no gradient.
+            return 0.0;
+        }
         let weighted_sum: f64 = input.iter()
             .zip(self.weights.iter())
             .zip(self.delays.iter())
             .map(|((&x, &w), &d)| x * w * self.temporal_kernel(time - d))
-            .sum();
-        
-        let activation = self.activation_function(weighted_sum);
-        self.activation_history.push((time, activation));
-        
-        if self.activation_history.len() > 100 {
-            self.activation_history.remove(0);
-        }
-        
-        activation
+            .sum::<f64>()
+            + self.bias;
+        self.activation_function(weighted_sum)
     }
 
     pub fn input_size(&self) -> usize {
         self.weights.len()
     }
 
+    /// Whether the last [`TemporalNeuron::activate`] output exceeded
+    /// `threshold`; false before the first activation or after a reset.
+    pub fn spike(&self, threshold: f64) -> bool {
+        self.activation_history.back().is_some_and(|&(_, activation)| activation > threshold)
+    }
+
+    /// Forgets every recorded activation and the last firing, as at construction.
+    pub fn reset_history(&mut self) {
+        self.activation_history.clear();
+        self.last_fire_time = None;
+    }
+
+    /// Forgets the last firing, so the next activation is not suppressed,
+    /// keeping the activation history.
+    pub fn clear_refractory(&mut self) {
+        self.last_fire_time = None;
+    }
+
+    /// Keeps only the inputs listed in `kept`, with their weights and delays,
+    /// after the layer feeding this neuron lost neurons.
+    pub(crate) fn keep_inputs(&mut self, kept: &[usize]) {
+        self.weights = kept.iter().map(|&i| self.weights[i]).collect();
+        self.delays = kept.iter().map(|&i| self.delays[i]).collect();
+        self.last_input = vec![0.0; kept.len()];
+        self.previous_delay_gradients.clear();
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Learned delay of each input, in the same time units as `activate`.
+    pub fn delays(&self) -> &[f64] {
+        &self.delays
+    }
+
+    /// `(min_delay, max_delay)` the delays are kept within.
+    pub fn delay_range(&self) -> (f64, f64) {
+        self.delay_range
+    }
+
+    pub fn kernel(&self) -> TemporalKernel {
+        self.kernel
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+
+    /// Gradient of the bias for `error` at the neuron's last output.
+    pub fn bias_gradient(&self, error: f64) -> f64 {
+        let (_, last_activation) = self.activation_history.back().unwrap();
+        error * self.activation_function_derivative(last_activation)
+    }
+
+    /// Gradient with respect to each input for `error` at the neuron's last output.
     pub fn calculate_gradients(&self, error: f64) -> Vec<f64> {
-        let (time, last_activation) = self.activation_history.last().unwrap();
+        let (time, last_activation) = self.activation_history.back().unwrap();
         let gradient = error * self.activation_function_derivative(last_activation);
         
         self.weights.iter()
@@ -50,24 +294,112 @@ impl TemporalNeuron {
             .collect()
     }
 
-    pub fn update_weights(&mut self, gradients: &[f64], learning_rate: f64) {
-        for ((weight, delay), &gradient) in self.weights.iter_mut()
-            .zip(self.delays.iter_mut())
-            .zip(gradients.iter()) {
-            *weight -= learning_rate * gradient;
+    /// Spike-timing-dependent plasticity: strengthens each weight whose input
+    /// arrived at or before `post_time`, when the neuron fired, and weakens each
+    /// one whose input arrived after it. The change is `plasticity *
+    /// exp(-|dt| / STDP_TAU)` for a timing difference `dt`. Non-finite entries of
+    /// `pre_times` mark inputs that did not spike; their weights are unchanged.
+    pub fn stdp_update(&mut self, pre_times: &[f64], post_time: f64) {
+        for (weight, &pre_time) in self.weights.iter_mut().zip(pre_times.iter()) {
+            if !pre_time.is_finite() {
+                continue;
+            }
+            let dt = post_time - pre_time;
+            let change = self.plasticity * (-dt.abs() / STDP_TAU).exp();
+            if dt >= 0.0 {
+                *weight += change;
+            } else {
+                *weight -= change;
+            }
+        }
+    }
+
+    /// Applies [`TemporalNeuron::stdp_update`] for the last activation if the
+    /// neuron fired. Input `i` counts as spiking when positive, arriving at its
+    /// delay, where the temporal kernel weighs it most.
+    fn stdp_after_activation(&mut self, input: &[f64], time: f64, activation: f64) {
+        if activation <= STDP_FIRING_THRESHOLD {
+            return;
+        }
+        let pre_times: Vec<f64> = input.iter()
+            .zip(self.delays.iter())
+            .map(|(&x, &d)| if x > 0.0 { d } else { f64::NAN })
+            .collect();
+        self.stdp_update(&pre_times, time);
+    }
+
+    /// Gradient of each weight for `error` at the neuron's last output.
+    pub fn weight_gradients(&self, error: f64) -> Vec<f64> {
+        let (time, last_activation) = self.activation_history.back().unwrap();
+        let gradient = error * self.activation_function_derivative(last_activation);
+
+        self.last_input.iter()
+            .zip(self.delays.iter())
+            .map(|(&x, &d)| gradient * x * self.temporal_kernel(*time - d))
+            .collect()
+    }
+
+    /// Gradient of each delay for `error` at the neuron's last output. Input `i`
+    /// is weighted by `kernel(time - delay_i)`, so its delay gradient carries
+    /// the kernel's derivative with the sign flipped.
+    pub fn delay_gradients(&self, error: f64) -> Vec<f64> {
+        let (time, last_activation) = self.activation_history.back().unwrap();
+        let gradient = error * self.activation_function_derivative(last_activation);
+
+        self.last_input.iter()
+            .zip(self.weights.iter())
+            .zip(self.delays.iter())
+            .map(|((&x, &w), &d)| -gradient * x * w * self.kernel.derivative(*time - d))
+            .collect()
+    }
+
+    /// Scale of the delay updates and of STDP weight changes. Drawn from
+    /// `0.0..0.1` at construction.
+    pub fn plasticity(&self) -> f64 {
+        self.plasticity
+    }
+
+    pub fn set_plasticity(&mut self, plasticity: f64) {
+        assert!(plasticity >= 0.0, "plasticity must not be negative");
+        self.plasticity = plasticity;
+    }
+
+    /// Enables or disables learning the plasticity in
+    /// [`TemporalNeuron::update_delays`]. Off by default.
+    pub fn set_learn_plasticity(&mut self, enabled: bool) {
+        self.learn_plasticity = enabled;
+        self.previous_delay_gradients.clear();
+    }
+
+    /// Moves each delay against its gradient from [`TemporalNeuron::delay_gradients`],
+    /// scaled by the neuron's plasticity, keeping delays within its delay range.
+    ///
+    /// When plasticity is learned, it first takes a hypergradient step: the
+    /// previous delay update changes the loss by `-learning_rate * g · g_prev`
+    /// per unit of plasticity, so plasticity grows by `learning_rate * g · g_prev`
+    /// while successive gradients agree and shrinks when they oscillate. It
+    /// stays within `[0, 1]`.
+    pub fn update_delays(&mut self, gradients: &[f64], learning_rate: f64) {
+        if self.learn_plasticity {
+            if self.previous_delay_gradients.len() == gradients.len() {
+                let agreement: f64 = gradients.iter().zip(&self.previous_delay_gradients).map(|(g, previous)| g * previous).sum();
+                self.plasticity = (self.plasticity + learning_rate * agreement).clamp(0.0, MAX_PLASTICITY);
+            }
+            self.previous_delay_gradients.clear();
+            self.previous_delay_gradients.extend_from_slice(gradients);
+        }
+        for (delay, &gradient) in self.delays.iter_mut().zip(gradients.iter()) {
             *delay -= learning_rate * self.plasticity * gradient;
-            *delay = delay.clamp(0.0, 1.0); // Ensure delay stays in [0, 1]
+            *delay = delay.clamp(self.delay_range.0, self.delay_range.1);
         }
     }
 
     fn temporal_kernel(&self, t: f64) -> f64 {
-        // Using a simple exponential decay kernel
-        (-t.abs()).exp()
+        self.kernel.evaluate(t)
     }
 
     fn activation_function(&self, x: f64) -> f64 {
-        // Sigmoid activation function
-        1.0 / (1.0 + (-x).exp())
+        sigmoid(x)
     }
 
     fn activation_function_derivative(&self, y: &f64) -> f64 {
@@ -76,36 +408,516 @@ impl TemporalNeuron {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TemporalLayer {
     pub neurons: Vec<TemporalNeuron>,
+    // Input width, kept separately so a layer without neurons still knows it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    in_size: usize,
+    use_bias: bool,
+    stdp: bool,
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_optimizer"))]
+    optimizer: Box<dyn Optimizer>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gradient_sum: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bias_gradient_sum: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    delay_gradient_sum: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    accumulated_steps: usize,
 }
 
 impl TemporalLayer {
-    pub fn new(size: usize) -> Self {
+    /// A layer of `out_size` neurons, each fed from all `in_size` inputs.
+    pub fn new(in_size: usize, out_size: usize, kernel: TemporalKernel, history_length: usize) -> Self {
+        Self::with_rng(in_size, out_size, kernel, history_length, &mut rand::thread_rng())
+    }
+
+    /// `history_length` is the number of past activations each neuron keeps, at least 1.
+    pub fn with_rng<R: Rng + ?Sized>(in_size: usize, out_size: usize, kernel: TemporalKernel, history_length: usize, rng: &mut R) -> Self {
+        Self::with_init(in_size, out_size, kernel, WeightInit::default(), history_length, rng)
+    }
+
+    /// Like [`TemporalLayer::with_rng`], drawing the initial weights from `init`.
+    pub fn with_init<R: Rng + ?Sized>(
+        in_size: usize,
+        out_size: usize,
+        kernel: TemporalKernel,
+        init: WeightInit,
+        history_length: usize,
+        rng: &mut R,
+    ) -> Self {
+        Self::with_delay_range(in_size, out_size, kernel, init, history_length, DEFAULT_DELAY_RANGE, rng)
+    }
+
+    /// Like [`TemporalLayer::with_init`], with every neuron's delays drawn from
+    /// and kept within `delay_range`; see [`TemporalNeuron::with_delay_range`].
+    pub fn with_delay_range<R: Rng + ?Sized>(
+        in_size: usize,
+        out_size: usize,
+        kernel: TemporalKernel,
+        init: WeightInit,
+        history_length: usize,
+        delay_range: (f64, f64),
+        rng: &mut R,
+    ) -> Self {
         TemporalLayer {
-            neurons: (0..size).map(|_| TemporalNeuron::new(size)).collect(),
+            neurons: (0..out_size)
+                .map(|_| TemporalNeuron::with_delay_range(in_size, out_size, kernel, init, history_length, delay_range, rng))
+                .collect(),
+            in_size,
+            use_bias: true,
+            stdp: false,
+            optimizer: default_optimizer(),
+            gradient_sum: Vec::new(),
+            bias_gradient_sum: Vec::new(),
+            delay_gradient_sum: Vec::new(),
+            accumulated_steps: 0,
+        }
+    }
+
+    /// Replaces the optimizer used to apply this layer's weight updates. Delays
+    /// are still updated by plain gradient descent scaled by each neuron's plasticity.
+    pub fn set_optimizer(&mut self, optimizer: Box<dyn Optimizer>) {
+        self.optimizer = optimizer;
+    }
+
+    /// Enables or disables the learnable per-neuron bias. Disabling it resets
+    /// every bias to zero.
+    pub fn set_use_bias(&mut self, use_bias: bool) {
+        self.use_bias = use_bias;
+        if !use_bias {
+            for neuron in &mut self.neurons {
+                neuron.bias = 0.0;
+            }
         }
     }
 
+    /// Enables or disables unsupervised STDP, which then adjusts the weights of
+    /// every neuron that fires during [`TemporalLayer::forward`]. Off by default;
+    /// the supervised gradient updates are unaffected either way.
+    pub fn set_stdp(&mut self, enabled: bool) {
+        self.stdp = enabled;
+    }
+
+    /// [`TemporalNeuron::spike`] of every neuron for the last forward pass.
+    pub fn spike_train(&self, threshold: f64) -> Vec<bool> {
+        self.neurons.iter().map(|neuron| neuron.spike(threshold)).collect()
+    }
+
+    /// Sets the plasticity of every neuron; see [`TemporalNeuron::set_plasticity`].
+    pub fn set_plasticity(&mut self, plasticity: f64) {
+        for neuron in &mut self.neurons {
+            neuron.set_plasticity(plasticity);
+        }
+    }
+
+    /// Enables or disables learned plasticity in every neuron; see
+    /// [`TemporalNeuron::update_delays`].
+    pub fn set_learn_plasticity(&mut self, enabled: bool) {
+        for neuron in &mut self.neurons {
+            neuron.set_learn_plasticity(enabled);
+        }
+    }
+
+    /// Sets the refractory period of every neuron; see
+    /// [`TemporalNeuron::set_refractory`].
+    pub fn set_refractory(&mut self, refractory_period: f64, fire_threshold: f64) {
+        for neuron in &mut self.neurons {
+            neuron.set_refractory(refractory_period, fire_threshold);
+        }
+    }
+
+    /// Clears every neuron's activation history, e.g. between independent sequences.
+    pub fn reset_history(&mut self) {
+        for neuron in &mut self.neurons {
+            neuron.reset_history();
+        }
+    }
+
+    /// Forgets every neuron's last firing; see [`TemporalNeuron::clear_refractory`].
+    pub fn clear_refractory(&mut self) {
+        for neuron in &mut self.neurons {
+            neuron.clear_refractory();
+        }
+    }
+
+    /// Keeps only the inputs listed in `kept`; see [`TemporalNeuron::keep_inputs`].
+    pub(crate) fn keep_inputs(&mut self, kept: &[usize]) {
+        let rows: Vec<Option<usize>> = (0..self.neurons.len()).map(Some).collect();
+        self.optimizer.remap_state(&matrix_state_sources((self.neurons.len(), self.input_size()), &rows, kept));
+        for neuron in &mut self.neurons {
+            neuron.keep_inputs(kept);
+        }
+        self.in_size = kept.len();
+        self.clear_gradients();
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.neurons.first().map_or(self.in_size, |neuron| neuron.input_size())
+    }
+
     pub fn forward(&mut self, input: &[f64], time: f64) -> Vec<f64> {
-        self.neurons
-            .iter_mut()
-            .map(|neuron| neuron.activate(input, time))
+        #[cfg(not(feature = "rayon"))]
+        let neurons = self.neurons.iter_mut();
+        #[cfg(feature = "rayon")]
+        let neurons = self.neurons.par_iter_mut();
+
+        let stdp = self.stdp;
+        neurons
+            .map(|neuron| {
+                let activation = neuron.activate(input, time);
+                if stdp {
+                    neuron.stdp_after_activation(input, time, activation);
+                }
+                activation
+            })
             .collect()
     }
 
-    pub fn backward(&mut self, error: &[f64], learning_rate: f64) -> Vec<f64> {
-        let mut next_error = vec![0.0; self.neurons[0].input_size()];
+    /// Outputs of [`TemporalLayer::forward`] without recording any history or
+    /// applying STDP.
+    pub fn infer(&self, input: &[f64], time: f64) -> Vec<f64> {
+        self.neurons.iter().map(|neuron| neuron.output(input, time)).collect()
+    }
 
-        for (neuron, &neuron_error) in self.neurons.iter_mut().zip(error.iter()) {
-            let neuron_gradients = neuron.calculate_gradients(neuron_error);
-            neuron.update_weights(&neuron_gradients, learning_rate);
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.optimizer.set_learning_rate(learning_rate);
+    }
 
-            for (i, &gradient) in neuron_gradients.iter().enumerate() {
+    pub fn learning_rate(&self) -> f64 {
+        self.optimizer.learning_rate()
+    }
+
+    /// Sum of squared weights, used for the L2 penalty.
+    pub fn squared_weight_sum(&self) -> f64 {
+        self.neurons.iter().flat_map(|neuron| neuron.weights.iter()).map(|w| w * w).sum()
+    }
+
+    /// Mutable access to weight `index % input_size` of neuron
+    /// `index / input_size`, for gradient checking.
+    pub(crate) fn weight_mut(&mut self, index: usize) -> &mut f64 {
+        let input_size = self.input_size();
+        &mut self.neurons[index / input_size].weights[index % input_size]
+    }
+
+    /// Accumulated gradient of the weight addressed as in [`TemporalLayer::weight_mut`].
+    pub(crate) fn weight_gradient(&self, index: usize) -> f64 {
+        self.gradient_sum.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Describes every weight or delay holding NaN or an infinity, as
+    /// `neuron N weight I` or `neuron N delay I`.
+    pub fn non_finite_parameters(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for (n, neuron) in self.neurons.iter().enumerate() {
+            for (i, weight) in neuron.weights.iter().enumerate() {
+                if !weight.is_finite() {
+                    found.push(format!("neuron {} weight {}", n, i));
+                }
+            }
+            for (i, delay) in neuron.delays.iter().enumerate() {
+                if !delay.is_finite() {
+                    found.push(format!("neuron {} delay {}", n, i));
+                }
+            }
+            if !neuron.bias.is_finite() {
+                found.push(format!("neuron {} bias", n));
+            }
+        }
+        found
+    }
+
+    /// Backpropagates `error` and immediately applies the resulting update.
+    pub fn backward(&mut self, error: &[f64]) -> Vec<f64> {
+        let next_error = self.accumulate_gradients(error);
+        self.apply_gradients(0.0, None);
+        next_error
+    }
+
+    /// Backpropagates `error`, adding the gradients to the layer's running sum
+    /// instead of applying them. Call [`TemporalLayer::apply_gradients`] to update.
+    pub fn accumulate_gradients(&mut self, error: &[f64]) -> Vec<f64> {
+        let input_size = self.input_size();
+        let mut next_error = vec![0.0; input_size];
+        self.gradient_sum.resize(self.neurons.len() * input_size, 0.0);
+        self.bias_gradient_sum.resize(self.neurons.len(), 0.0);
+        self.delay_gradient_sum.resize(self.neurons.len() * input_size, 0.0);
+
+        for (n, (neuron, &neuron_error)) in self.neurons.iter().zip(error.iter()).enumerate() {
+            if self.use_bias {
+                self.bias_gradient_sum[n] += neuron.bias_gradient(neuron_error);
+            }
+
+            for (i, gradient) in neuron.calculate_gradients(neuron_error).into_iter().enumerate() {
                 next_error[i] += gradient;
             }
+            for (i, gradient) in neuron.weight_gradients(neuron_error).into_iter().enumerate() {
+                self.gradient_sum[n * input_size + i] += gradient;
+            }
+            for (i, gradient) in neuron.delay_gradients(neuron_error).into_iter().enumerate() {
+                self.delay_gradient_sum[n * input_size + i] += gradient;
+            }
         }
 
+        self.accumulated_steps += 1;
         next_error
     }
-}
\ No newline at end of file
+
+    /// Applies the gradients accumulated since the last call, averaged over the
+    /// number of accumulated examples, to both weights and delays. The averaged
+    /// weight and bias gradient, and separately the delay gradient, are clipped
+    /// to an L2 norm of `max_grad_norm` if given, and the weight gradients also
+    /// receive an L2 decay term of `l2_lambda * weight`.
+    pub fn apply_gradients(&mut self, l2_lambda: f64, max_grad_norm: Option<f64>) {
+        let input_size = self.input_size();
+        if self.accumulated_steps == 0 || input_size == 0 {
+            return;
+        }
+
+        // Weights and biases go through the optimizer as one parameter vector,
+        // so that stateful optimizers see a consistent length.
+        let learning_rate = self.optimizer.learning_rate();
+        let scale = 1.0 / self.accumulated_steps as f64;
+        let mut gradients: Vec<f64> = self.gradient_sum.iter().chain(self.bias_gradient_sum.iter()).map(|&g| g * scale).collect();
+        if let Some(max_norm) = max_grad_norm {
+            clip_grad_norm(&mut gradients, max_norm);
+        }
+        let mut parameters: Vec<f64> = self.neurons.iter()
+            .flat_map(|neuron| neuron.weights.iter().copied())
+            .chain(self.neurons.iter().map(|neuron| neuron.bias))
+            .collect();
+        let weight_count = self.gradient_sum.len();
+        let parameter_gradients: Vec<f64> = gradients.iter()
+            .zip(parameters.iter())
+            .enumerate()
+            .map(|(i, (&g, &p))| if i < weight_count { g + l2_lambda * p } else { g })
+            .collect();
+        self.optimizer.step(&mut parameters, &parameter_gradients);
+
+        let mut delay_gradients: Vec<f64> = self.delay_gradient_sum.iter().map(|&g| g * scale).collect();
+        if let Some(max_norm) = max_grad_norm {
+            clip_grad_norm(&mut delay_gradients, max_norm);
+        }
+
+        let (weights, biases) = parameters.split_at(weight_count);
+        for (((neuron, weight_chunk), gradient_chunk), &bias) in self.neurons.iter_mut()
+            .zip(weights.chunks(input_size))
+            .zip(delay_gradients.chunks(input_size))
+            .zip(biases) {
+            neuron.weights.copy_from_slice(weight_chunk);
+            neuron.bias = bias;
+            neuron.update_delays(gradient_chunk, learning_rate);
+        }
+
+        self.clear_gradients();
+    }
+
+    /// Discards the gradients accumulated since the last update without applying them.
+    pub fn clear_gradients(&mut self) {
+        self.gradient_sum.clear();
+        self.bias_gradient_sum.clear();
+        self.delay_gradient_sum.clear();
+        self.accumulated_steps = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::Adam;
+
+    #[test]
+    fn test_default_kernel_matches_unit_exponential() {
+        let kernel = TemporalKernel::default();
+        for &t in &[-2.0, -0.5, 0.0, 0.5, 2.0] {
+            assert_eq!(kernel.evaluate(t), (-f64::abs(t)).exp());
+        }
+    }
+
+    #[test]
+    fn test_layer_without_neurons_passes_back_zero_error() {
+        let mut layer = TemporalLayer::new(3, 0, TemporalKernel::default(), DEFAULT_HISTORY_LENGTH);
+        assert_eq!(layer.input_size(), 3);
+        assert!(layer.forward(&[0.1, 0.2, 0.3], 0.0).is_empty());
+        assert_eq!(layer.backward(&[]), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_delay_gradients_match_finite_differences() {
+        let kernels = [
+            TemporalKernel::default(),
+            TemporalKernel::Gaussian { sigma: 0.7 },
+            TemporalKernel::Alpha { tau: 0.5 },
+        ];
+        let input = [0.8, -0.4, 0.3];
+        let time = 1.2;
+        let epsilon = 1e-6;
+        for kernel in kernels {
+            let mut neuron = TemporalNeuron::with_rng(3, kernel, &mut rand::thread_rng());
+            neuron.delays = vec![0.1, 0.5, 0.9];
+            neuron.activate(&input, time);
+            let analytic = neuron.delay_gradients(1.0);
+
+            for (i, &gradient) in analytic.iter().enumerate() {
+                let output_at = |offset: f64| {
+                    let mut shifted = neuron.clone();
+                    shifted.delays[i] += offset;
+                    shifted.output(&input, time)
+                };
+                let numeric = (output_at(epsilon) - output_at(-epsilon)) / (2.0 * epsilon);
+                assert!((gradient - numeric).abs() < 1e-6, "{:?} delay {}: {} vs {}", kernel, i, gradient, numeric);
+            }
+        }
+    }
+
+    #[test]
+    fn test_history_keeps_the_most_recent_activations() {
+        let mut layer = TemporalLayer::new(1, 1, TemporalKernel::default(), 2);
+        for step in 0..4 {
+            layer.forward(&[1.0], step as f64);
+        }
+        let times: Vec<f64> = layer.neurons[0].activation_history.iter().map(|&(time, _)| time).collect();
+        assert_eq!(times, vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_spike_train_thresholds_the_last_activation() {
+        let mut layer = TemporalLayer::new(1, 2, TemporalKernel::default(), DEFAULT_HISTORY_LENGTH);
+        for (neuron, weight) in layer.neurons.iter_mut().zip([5.0, -5.0]) {
+            neuron.weights = vec![weight];
+            neuron.delays = vec![0.0];
+            neuron.bias = 0.0;
+        }
+        assert_eq!(layer.spike_train(0.5), vec![false, false]);
+        layer.forward(&[1.0], 0.5);
+        assert_eq!(layer.spike_train(0.5), vec![true, false]);
+        assert_eq!(layer.spike_train(0.0), vec![true, true]);
+        layer.reset_history();
+        assert_eq!(layer.spike_train(0.5), vec![false, false]);
+    }
+
+    #[test]
+    fn test_refractory_period_suppresses_firing_and_its_gradient() {
+        let mut neuron = TemporalNeuron::with_rng(1, TemporalKernel::default(), &mut rand::thread_rng());
+        neuron.weights = vec![20.0];
+        neuron.delays = vec![0.0];
+        neuron.set_refractory(2.0, 0.5);
+
+        assert!(neuron.activate(&[1.0], 0.0) > 0.5);
+        assert!(neuron.is_refractory(1.0));
+        assert_eq!(neuron.activate(&[1.0], 1.0), 0.0);
+        assert_eq!(neuron.weight_gradients(1.0), vec![0.0]);
+        assert_eq!(neuron.delay_gradients(1.0), vec![0.0]);
+        assert_eq!(neuron.calculate_gradients(1.0), vec![0.0]);
+        assert_eq!(neuron.bias_gradient(1.0), 0.0);
+
+        // The period has run out by t = 2, so the neuron fires again.
+        assert!(neuron.activate(&[1.0], 2.0) > 0.5);
+        neuron.reset_history();
+        assert!(!neuron.is_refractory(2.5));
+    }
+
+    #[test]
+    fn test_delays_start_and_stay_within_the_delay_range() {
+        let mut rng = rand::thread_rng();
+        let mut neuron = TemporalNeuron::with_delay_range(50, 1, TemporalKernel::default(), WeightInit::default(), DEFAULT_HISTORY_LENGTH, (2.0, 10.0), &mut rng);
+        assert_eq!(neuron.delay_range(), (2.0, 10.0));
+        assert!(neuron.delays().iter().all(|delay| (2.0..10.0).contains(delay)));
+        // Most draws land beyond the default range's upper bound of 1.
+        assert!(neuron.delays().iter().filter(|&&delay| delay > 5.0).count() > 10);
+
+        neuron.set_plasticity(1.0);
+        let gradients: Vec<f64> = (0..50).map(|i| if i % 2 == 0 { 100.0 } else { -100.0 }).collect();
+        neuron.update_delays(&gradients, 1.0);
+        for (i, &delay) in neuron.delays().iter().enumerate() {
+            assert_eq!(delay, if i % 2 == 0 { 2.0 } else { 10.0 });
+        }
+
+        let fixed = TemporalNeuron::with_delay_range(3, 1, TemporalKernel::default(), WeightInit::default(), DEFAULT_HISTORY_LENGTH, (4.0, 4.0), &mut rng);
+        assert_eq!(fixed.delays(), &[4.0; 3]);
+        assert_eq!(TemporalNeuron::new(3).delay_range(), DEFAULT_DELAY_RANGE);
+    }
+
+    #[test]
+    fn test_optimizer_state_follows_the_kept_inputs() {
+        let mut layer = TemporalLayer::new(3, 2, TemporalKernel::default(), DEFAULT_HISTORY_LENGTH);
+        layer.set_optimizer(Box::new(Adam::new(0.05)));
+        // Input 0 is silent, so dropping it leaves the other gradients unchanged.
+        let input = [0.0, 0.6, -0.3];
+        for _ in 0..3 {
+            layer.forward(&input, 0.5);
+            layer.backward(&[0.4, -0.2]);
+        }
+
+        let mut pruned = layer.clone();
+        pruned.keep_inputs(&[1, 2]);
+        layer.forward(&input, 0.5);
+        layer.backward(&[0.4, -0.2]);
+        pruned.forward(&input[1..], 0.5);
+        pruned.backward(&[0.4, -0.2]);
+        for (kept, full) in pruned.neurons.iter().zip(&layer.neurons) {
+            for (a, b) in kept.weights.iter().zip(&full.weights[1..]) {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_learned_plasticity_follows_gradient_agreement() {
+        let mut neuron = TemporalNeuron::with_rng(2, TemporalKernel::default(), &mut rand::thread_rng());
+        neuron.set_plasticity(0.05);
+        neuron.update_delays(&[0.2, -0.1], 0.5);
+        neuron.update_delays(&[0.2, -0.1], 0.5);
+        assert_eq!(neuron.plasticity(), 0.05);
+
+        neuron.set_learn_plasticity(true);
+        neuron.update_delays(&[0.2, -0.1], 0.5);
+        assert_eq!(neuron.plasticity(), 0.05);
+        neuron.update_delays(&[0.2, -0.1], 0.5);
+        assert!((neuron.plasticity() - (0.05 + 0.5 * 0.05)).abs() < 1e-12);
+        neuron.update_delays(&[-0.4, 0.2], 0.5);
+        assert!((neuron.plasticity() - 0.025).abs() < 1e-12);
+        neuron.update_delays(&[0.8, -0.2], 0.5);
+        assert_eq!(neuron.plasticity(), 0.0);
+    }
+
+    #[test]
+    fn test_stdp_strengthens_causal_and_weakens_late_inputs() {
+        let mut neuron = TemporalNeuron::with_rng(3, TemporalKernel::default(), &mut rand::thread_rng());
+        neuron.plasticity = 0.1;
+        let before = neuron.weights.clone();
+
+        neuron.stdp_update(&[0.5, 1.5, f64::NAN], 1.0);
+        let expected = 0.1 * (-0.5f64).exp();
+        assert!((neuron.weights[0] - (before[0] + expected)).abs() < 1e-12);
+        assert!((neuron.weights[1] - (before[1] - expected)).abs() < 1e-12);
+        assert_eq!(neuron.weights[2], before[2]);
+    }
+
+    #[test]
+    fn test_stdp_runs_in_forward_only_when_enabled() {
+        let mut layer = TemporalLayer::new(2, 2, TemporalKernel::default(), DEFAULT_HISTORY_LENGTH);
+        for neuron in &mut layer.neurons {
+            neuron.weights = vec![5.0, 5.0];
+            neuron.plasticity = 0.05;
+        }
+        layer.forward(&[1.0, 1.0], 0.5);
+        assert!(layer.neurons.iter().all(|neuron| neuron.weights == [5.0, 5.0]));
+
+        layer.set_stdp(true);
+        layer.forward(&[1.0, 1.0], 0.5);
+        assert!(layer.neurons.iter().all(|neuron| neuron.weights != [5.0, 5.0]));
+    }
+
+    #[test]
+    fn test_alpha_kernel_peaks_at_tau() {
+        let kernel = TemporalKernel::Alpha { tau: 2.0 };
+        assert_eq!(kernel.evaluate(-1.0), 0.0);
+        assert!((kernel.evaluate(2.0) - 1.0).abs() < 1e-12);
+        assert!(kernel.evaluate(1.0) < 1.0);
+        assert!(kernel.evaluate(3.0) < 1.0);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::EpochStats;
+
+/// The `mean_error` of every epoch in `stats`, in order.
+pub fn loss_history(stats: &[EpochStats]) -> Vec<f64> {
+    stats.iter().map(|epoch| epoch.mean_error).collect()
+}
+
+/// Whether training has settled: true once the standard deviation of the last
+/// `window` entries of `losses` is below `tolerance`. Returns false while
+/// fewer than `window` losses are available, or if `window` is zero.
+pub fn has_converged(losses: &[f64], window: usize, tolerance: f64) -> bool {
+    if window == 0 || losses.len() < window {
+        return false;
+    }
+    let recent = &losses[losses.len() - window..];
+    let mean = recent.iter().sum::<f64>() / window as f64;
+    let variance = recent.iter().map(|&loss| (loss - mean).powi(2)).sum::<f64>() / window as f64;
+    variance.sqrt() < tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_converged_looks_at_the_last_window() {
+        let losses = [1.0, 0.5, 0.3, 0.2001, 0.2, 0.2002];
+        assert!(has_converged(&losses, 3, 1e-3));
+        assert!(!has_converged(&losses, 4, 1e-3));
+        assert!(!has_converged(&losses[..2], 3, 1.0));
+        assert!(!has_converged(&losses, 0, 1.0));
+    }
+
+    #[test]
+    fn test_loss_history_of_a_training_run() {
+        let mut network = crate::NeuroForge::with_seed(&[2, 2], &[false, false], &[true, true], 3);
+        let stats = network.train(&[vec![0.0, 1.0]], &[vec![1.0, 0.0]], 5, 0.0);
+        let losses = loss_history(&stats);
+        assert_eq!(losses.len(), 5);
+        assert!(has_converged(&losses, 5, 1e-12));
+    }
+}